@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use crate::faults::{Fault, FaultWindow};
+use crate::pose::HeadPose;
+use crate::scoring::PostureScore;
+use crate::session::PresenceGap;
+
+/// Min / mean / max of one measurement over a session.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Summary {
+    pub min: f32,
+    pub mean: f32,
+    pub max: f32,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Running {
+    count: u64,
+    sum: f64,
+    min: f32,
+    max: f32,
+}
+
+impl Running {
+    fn add(&mut self, value: f32) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        }
+        self.count += 1;
+        self.sum += value as f64;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn summary(&self) -> Option<Summary> {
+        (self.count > 0).then(|| Summary { min: self.min, mean: (self.sum / self.count as f64) as f32, max: self.max })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct HeadPoseBreakdown {
+    samples: u64,
+    pitch_deg: Summary,
+    yaw_deg: Summary,
+    roll_deg: Summary,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ScoreBreakdown {
+    samples: u64,
+    total: Summary,
+    neck: Summary,
+    shoulders: Summary,
+    spine: Summary,
+    /// Share of scored frames that were good posture, 0-1.
+    good_fraction: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FaultBreakdown {
+    seconds: f64,
+    mean_confidence: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct StillnessBreakdown {
+    /// Shoulder widths per second.
+    motion_energy: Option<Summary>,
+    /// Still periods of a minute or more.
+    still_periods: u64,
+    longest_still_sec: f64,
+    total_still_sec: f64,
+    nudges: u64,
+}
+
+/// What the backend measured during the current session. `session::store` (automatic
+/// sessions) and `save_session` (the webview's) merge it in before storing.
+#[derive(Debug, Clone, Default)]
+pub struct SessionBreakdown {
+    pitch: Running,
+    yaw: Running,
+    roll: Running,
+    total: Running,
+    neck: Running,
+    shoulders: Running,
+    spine: Running,
+    good_frames: u64,
+    gaps: Vec<PresenceGap>,
+    // Time each fault was seen, and its confidence over those windows
+    faults: BTreeMap<Fault, (f64, Running)>,
+    motion: Running,
+    still_periods: u64,
+    longest_still: f64,
+    total_still: f64,
+    nudges: u64,
+}
+
+impl SessionBreakdown {
+    pub fn record_head_pose(&mut self, pose: &HeadPose) {
+        self.pitch.add(pose.pitch_deg);
+        self.yaw.add(pose.yaw_deg);
+        self.roll.add(pose.roll_deg);
+    }
+
+    pub fn record_score(&mut self, score: &PostureScore) {
+        self.total.add(score.total as f32);
+        self.neck.add(score.neck as f32);
+        self.shoulders.add(score.shoulders as f32);
+        self.spine.add(score.spine as f32);
+        if score.is_good {
+            self.good_frames += 1;
+        }
+    }
+
+    pub fn record_gap(&mut self, gap: PresenceGap) {
+        self.gaps.push(gap);
+    }
+
+    pub fn record_faults(&mut self, window: &FaultWindow) {
+        for detected in &window.faults {
+            let (seconds, confidence) = self.faults.entry(detected.fault).or_default();
+            *seconds += window.duration.as_secs_f64();
+            confidence.add(detected.confidence);
+        }
+    }
+
+    pub fn record_motion(&mut self, energy: f32) {
+        self.motion.add(energy);
+    }
+
+    pub fn record_still_period(&mut self, period: Duration) {
+        self.still_periods += 1;
+        self.longest_still = self.longest_still.max(period.as_secs_f64());
+        self.total_still += period.as_secs_f64();
+    }
+
+    pub fn record_nudge(&mut self) {
+        self.nudges += 1;
+    }
+
+    fn stillness(&self) -> Option<StillnessBreakdown> {
+        (self.motion.count > 0 || self.still_periods > 0).then(|| StillnessBreakdown {
+            motion_energy: self.motion.summary(),
+            still_periods: self.still_periods,
+            longest_still_sec: self.longest_still,
+            total_still_sec: self.total_still,
+            nudges: self.nudges,
+        })
+    }
+
+    fn head_pose(&self) -> Option<HeadPoseBreakdown> {
+        Some(HeadPoseBreakdown {
+            samples: self.pitch.count,
+            pitch_deg: self.pitch.summary()?,
+            yaw_deg: self.yaw.summary()?,
+            roll_deg: self.roll.summary()?,
+        })
+    }
+
+    fn score(&self) -> Option<ScoreBreakdown> {
+        Some(ScoreBreakdown {
+            samples: self.total.count,
+            total: self.total.summary()?,
+            neck: self.neck.summary()?,
+            shoulders: self.shoulders.summary()?,
+            spine: self.spine.summary()?,
+            good_fraction: self.good_frames as f32 / self.total.count as f32,
+        })
+    }
+
+    /// Adds our keys to the webview's breakdown JSON, keeping everything it already has.
+    /// Anything that is not a JSON object is replaced.
+    pub fn merge_into(&self, breakdown_json: &str) -> String {
+        let mut map = match serde_json::from_str::<Value>(breakdown_json) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        if let Some(head_pose) = self.head_pose().and_then(|h| serde_json::to_value(h).ok()) {
+            map.insert("head_pose".to_string(), head_pose);
+        }
+        if let Some(score) = self.score().and_then(|s| serde_json::to_value(s).ok()) {
+            map.insert("score".to_string(), score);
+        }
+        if !self.faults.is_empty() {
+            let faults: BTreeMap<Fault, FaultBreakdown> = self.faults.iter()
+                .filter_map(|(&fault, (seconds, confidence))| Some((fault, FaultBreakdown {
+                    seconds: *seconds,
+                    mean_confidence: confidence.summary()?.mean,
+                })))
+                .collect();
+            if let Ok(faults) = serde_json::to_value(faults) {
+                map.insert("faults".to_string(), faults);
+            }
+        }
+        if let Some(stillness) = self.stillness().and_then(|s| serde_json::to_value(s).ok()) {
+            map.insert("stillness".to_string(), stillness);
+        }
+        if !self.gaps.is_empty() {
+            if let Ok(gaps) = serde_json::to_value(&self.gaps) {
+                map.insert("presence_gaps".to_string(), gaps);
+            }
+        }
+        Value::Object(map).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_keeps_webview_scores_and_adds_head_pose() {
+        let mut breakdown = SessionBreakdown::default();
+        breakdown.record_head_pose(&HeadPose { pitch_deg: 10.0, yaw_deg: -4.0, roll_deg: 2.0 });
+        breakdown.record_head_pose(&HeadPose { pitch_deg: 20.0, yaw_deg: 4.0, roll_deg: 0.0 });
+
+        let merged: Value = serde_json::from_str(&breakdown.merge_into(r#"{"neck": 82}"#)).unwrap();
+        assert_eq!(merged["neck"], 82);
+        assert_eq!(merged["head_pose"]["samples"], 2);
+        assert_eq!(merged["head_pose"]["pitch_deg"]["mean"], 15.0);
+        assert_eq!(merged["head_pose"]["pitch_deg"]["max"], 20.0);
+        assert_eq!(merged["head_pose"]["yaw_deg"]["min"], -4.0);
+        assert!(merged.get("score").is_none());
+    }
+
+    #[test]
+    fn scores_are_summarised() {
+        let mut breakdown = SessionBreakdown::default();
+        breakdown.record_score(&PostureScore { total: 90, neck: 90, shoulders: 90, spine: 90, is_good: true });
+        breakdown.record_score(&PostureScore { total: 60, neck: 50, shoulders: 80, spine: 60, is_good: false });
+
+        let merged: Value = serde_json::from_str(&breakdown.merge_into("{}")).unwrap();
+        assert_eq!(merged["score"]["samples"], 2);
+        assert_eq!(merged["score"]["total"]["mean"], 75.0);
+        assert_eq!(merged["score"]["neck"]["min"], 50.0);
+        assert_eq!(merged["score"]["good_fraction"], 0.5);
+    }
+
+    #[test]
+    fn time_per_fault_is_summed() {
+        use crate::faults::DetectedFault;
+        use std::time::Duration;
+
+        let mut breakdown = SessionBreakdown::default();
+        let window = |confidence: f32| FaultWindow {
+            faults: vec![DetectedFault { fault: Fault::ForwardHead, confidence }],
+            duration: Duration::from_secs(2),
+        };
+        breakdown.record_faults(&window(0.6));
+        breakdown.record_faults(&window(1.0));
+        breakdown.record_faults(&FaultWindow { faults: vec![], duration: Duration::from_secs(2) });
+
+        let merged: Value = serde_json::from_str(&breakdown.merge_into("{}")).unwrap();
+        assert_eq!(merged["faults"]["forward_head"]["seconds"], 4.0);
+        assert_eq!(merged["faults"]["forward_head"]["mean_confidence"], 0.8);
+        assert!(merged["faults"].get("lean_left").is_none());
+    }
+
+    #[test]
+    fn stillness_is_summarised() {
+        let mut breakdown = SessionBreakdown::default();
+        breakdown.record_motion(0.5);
+        breakdown.record_motion(1.5);
+        breakdown.record_still_period(Duration::from_secs(600));
+        breakdown.record_still_period(Duration::from_secs(2400));
+        breakdown.record_nudge();
+
+        let merged: Value = serde_json::from_str(&breakdown.merge_into("{}")).unwrap();
+        assert_eq!(merged["stillness"]["motion_energy"]["mean"], 1.0);
+        assert_eq!(merged["stillness"]["still_periods"], 2);
+        assert_eq!(merged["stillness"]["longest_still_sec"], 2400.0);
+        assert_eq!(merged["stillness"]["total_still_sec"], 3000.0);
+        assert_eq!(merged["stillness"]["nudges"], 1);
+    }
+
+    #[test]
+    fn presence_gaps_are_listed() {
+        let mut breakdown = SessionBreakdown::default();
+        breakdown.record_gap(PresenceGap { start: "a".to_string(), end: "b".to_string(), duration_sec: 42 });
+
+        let merged: Value = serde_json::from_str(&breakdown.merge_into("{}")).unwrap();
+        assert_eq!(merged["presence_gaps"][0]["duration_sec"], 42);
+    }
+
+    #[test]
+    fn nothing_measured_leaves_breakdown_alone() {
+        assert_eq!(SessionBreakdown::default().merge_into(r#"{"neck":82}"#), r#"{"neck":82}"#);
+        assert_eq!(SessionBreakdown::default().merge_into("not json"), "{}");
+    }
+}
@@ -0,0 +1,371 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+use crate::pose::keypoints::*;
+use crate::pose::{Anchor, Landmark, PostureAngles};
+use crate::state::AppState;
+
+const DEFAULT_DURATION: Duration = Duration::from_secs(5);
+// About one second of usable frames at 15 FPS; anything less is not a baseline
+const MIN_SAMPLES: usize = 15;
+
+// Camera drift: the shoulders sit this far (normalised) from where calibration saw them,
+// or look this much bigger/smaller, for DRIFT_HOLD. Short enough to catch a moved laptop,
+// long enough that leaning over to grab something does not count.
+const DRIFT_SHIFT: f32 = 0.12;
+const DRIFT_SCALE: f32 = 0.25;
+const DRIFT_HOLD: Duration = Duration::from_secs(30);
+
+/// The user's own good posture: median landmarks and angles over a few seconds of capture.
+/// Scoring is relative to it once it exists.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    /// RFC 3339, local time.
+    pub created_at: String,
+    /// "name@version" of the model that captured it; landmarks from another model may sit differently.
+    pub model_version: Option<String>,
+    pub samples: usize,
+    pub landmarks: Vec<Landmark>,
+    pub angles: PostureAngles,
+}
+
+impl Baseline {
+    /// Medians rather than means, so a cough or a glance away during capture does not skew it.
+    pub fn from_samples(samples: &[(Vec<Landmark>, PostureAngles)], model_version: Option<String>) -> Result<Self, String> {
+        let samples: Vec<&(Vec<Landmark>, PostureAngles)> = samples.iter()
+            .filter(|(landmarks, _)| landmarks.len() == LANDMARK_COUNT)
+            .collect();
+        if samples.len() < MIN_SAMPLES {
+            return Err(format!("Only {} usable frames; sit in view and hold still", samples.len()));
+        }
+
+        let landmarks = (0..LANDMARK_COUNT).map(|i| {
+            let values = |f: fn(&Landmark) -> f32| median(samples.iter().map(|(lms, _)| f(&lms[i])));
+            Landmark {
+                x: values(|lm| lm.x).unwrap_or_default(),
+                y: values(|lm| lm.y).unwrap_or_default(),
+                z: values(|lm| lm.z).unwrap_or_default(),
+                visibility: values(|lm| lm.visibility).unwrap_or_default(),
+            }
+        }).collect();
+
+        let angle = |f: fn(&PostureAngles) -> Option<f32>| median(samples.iter().filter_map(|(_, a)| f(a)));
+        let angles = PostureAngles {
+            craniovertebral_deg: angle(|a| a.craniovertebral_deg),
+            neck_flexion_deg: angle(|a| a.neck_flexion_deg),
+            shoulder_tilt_deg: angle(|a| a.shoulder_tilt_deg),
+            trunk_inclination_deg: angle(|a| a.trunk_inclination_deg),
+            shoulder_protraction_deg: angle(|a| a.shoulder_protraction_deg),
+        };
+
+        Ok(Self { created_at: Local::now().to_rfc3339(), model_version, samples: samples.len(), landmarks, angles })
+    }
+
+    /// Where the user sat, so the primary-person selector can find them again after losing them.
+    pub fn anchor(&self) -> Option<Anchor> {
+        Anchor::of(&self.landmarks)
+    }
+
+    pub fn age_hours(&self) -> Option<f64> {
+        let created = DateTime::parse_from_rfc3339(&self.created_at).ok()?;
+        Some((Local::now().fixed_offset() - created).num_seconds() as f64 / 3600.0)
+    }
+}
+
+pub fn median(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let mut values: Vec<f32> = values.collect();
+    if values.is_empty() { return None; }
+    values.sort_by(f32::total_cmp);
+    let mid = values.len() / 2;
+    Some(if values.len() % 2 == 0 { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] })
+}
+
+/// Watches for the camera (or chair) having moved since calibration.
+#[derive(Debug, Default)]
+pub struct DriftMonitor {
+    off_since: Option<Instant>,
+    drifted: bool,
+}
+
+impl DriftMonitor {
+    /// Returns the new state when it changes.
+    pub fn update(&mut self, baseline: &Baseline, landmarks: &[Landmark], now: Instant) -> Option<bool> {
+        let (Some(base), Some(current)) = (shoulder_frame(&baseline.landmarks), shoulder_frame(landmarks)) else {
+            return None;
+        };
+        let shift = ((current.mid_x - base.mid_x).powi(2) + (current.mid_y - base.mid_y).powi(2)).sqrt();
+        let scale = current.width / base.width;
+        let off = shift > DRIFT_SHIFT || (scale - 1.0).abs() > DRIFT_SCALE;
+
+        let drifted = if off {
+            let since = *self.off_since.get_or_insert(now);
+            now.duration_since(since) >= DRIFT_HOLD
+        } else {
+            self.off_since = None;
+            false
+        };
+
+        if drifted != self.drifted {
+            self.drifted = drifted;
+            return Some(drifted);
+        }
+        None
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Kinds of `calibration-alert`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    CameraMoved,
+    // Ergonomic setup, see ergonomics.rs
+    MonitorTooLow,
+    MonitorTooHigh,
+    TooClose,
+    TooFar,
+    OffCentre,
+}
+
+/// Emitted on `calibration-alert` (see ARCHITECTURE.md) when the setup needs the user's attention.
+#[derive(Debug, Clone, Serialize)]
+pub struct CalibrationAlert {
+    pub kind: AlertKind,
+    pub message: String,
+    /// What to change.
+    pub suggestion: String,
+}
+
+// A capture in progress: usable frames collected until `until`
+#[derive(Debug)]
+pub struct Capture {
+    pub until: Instant,
+    pub samples: Vec<(Vec<Landmark>, PostureAngles)>,
+}
+
+/// Lives in `AppState`. The inference worker feeds captures and checks drift;
+/// the commands below start captures and report status.
+#[derive(Debug, Default)]
+pub struct CalibrationState {
+    pub baseline: Option<Arc<Baseline>>,
+    pub capture: Option<Capture>,
+    pub drift: DriftMonitor,
+}
+
+/// What `get_calibration` reports.
+#[derive(Debug, Clone, Serialize)]
+pub struct CalibrationStatus {
+    pub is_calibrated: bool,
+    pub calibrating: bool,
+    pub created_at: Option<String>,
+    pub age_hours: Option<f64>,
+    pub samples: usize,
+    pub model_version: Option<String>,
+    pub angles: Option<PostureAngles>,
+    pub camera_drifted: bool,
+}
+
+impl CalibrationStatus {
+    pub fn of(state: &CalibrationState) -> Self {
+        let baseline = state.baseline.as_deref();
+        Self {
+            is_calibrated: baseline.is_some(),
+            calibrating: state.capture.is_some(),
+            created_at: baseline.map(|b| b.created_at.clone()),
+            age_hours: baseline.and_then(Baseline::age_hours),
+            samples: baseline.map(|b| b.samples).unwrap_or_default(),
+            model_version: baseline.and_then(|b| b.model_version.clone()),
+            angles: baseline.map(|b| b.angles),
+            camera_drifted: state.drift.drifted,
+        }
+    }
+}
+
+pub fn store_baseline(conn: &Connection, baseline: &Baseline) -> Result<(), String> {
+    let json = serde_json::to_string(baseline).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO calibration (id, created_at, baseline_json) VALUES (1, ?1, ?2)",
+        params![baseline.created_at, json],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn load_baseline(conn: &Connection) -> Result<Option<Baseline>, String> {
+    let json: Option<String> = conn.query_row("SELECT baseline_json FROM calibration WHERE id = 1", [], |r| r.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    json.map(|j| serde_json::from_str(&j).map_err(|e| e.to_string())).transpose()
+}
+
+// Brings the cached baseline in line with the DB (it may have been created by an earlier run)
+fn sync_from_db(state: &AppState) -> Result<(), String> {
+    let baseline = {
+        let db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
+        let conn = db_lock.as_ref().ok_or("Database not initialized")?;
+        load_baseline(conn)?
+    };
+    let mut calibration = state.calibration.lock().map_err(|_| "Failed to lock calibration")?;
+    if calibration.capture.is_none() {
+        calibration.baseline = baseline.map(Arc::new);
+    }
+    Ok(())
+}
+
+/// Loads the stored baseline into `AppState` for a new tracking run; logs instead of failing.
+pub fn load_into_state(state: &AppState) {
+    if let Err(e) = sync_from_db(state) {
+        println!("⚠️ Calibration not loaded: {}", e);
+    }
+}
+
+/// Starts capturing the user's good posture for `duration_sec` (default 5). Tracking must be
+/// running; the worker emits `calibration_complete` or `calibration_failed` when done.
+/// Calling it again recalibrates.
+#[tauri::command]
+pub fn calibrate(state: State<AppState>, duration_sec: Option<u64>) -> Result<String, String> {
+    if !crate::tracking::IS_TRACKING.load(std::sync::atomic::Ordering::Relaxed) {
+        return Err("Start tracking before calibrating".to_string());
+    }
+    let duration = duration_sec.map(Duration::from_secs).unwrap_or(DEFAULT_DURATION);
+    let mut calibration = state.calibration.lock().map_err(|_| "Failed to lock calibration")?;
+    calibration.capture = Some(Capture { until: Instant::now() + duration, samples: Vec::new() });
+    println!("📐 Calibrating for {}s", duration.as_secs());
+    Ok("Calibrating".to_string())
+}
+
+/// Inference-worker side, once per processed frame: feeds a running capture (usable frames
+/// only), stores the baseline when it ends, and raises `calibration-alert` on camera drift.
+/// Returns true when a new baseline was just stored.
+pub fn observe(app_handle: &AppHandle, state: &AppState, landmarks: &[Landmark], angles: &PostureAngles, usable: bool) -> bool {
+    let Ok(mut calibration) = state.calibration.lock() else { return false };
+    let now = Instant::now();
+
+    if let Some(capture) = calibration.capture.as_mut() {
+        if usable {
+            capture.samples.push((landmarks.to_vec(), *angles));
+        }
+        if now < capture.until {
+            return false;
+        }
+        let samples = calibration.capture.take().map(|c| c.samples).unwrap_or_default();
+        let model_version = state.pose_engine.active_model().map(|m| m.version_tag());
+        let stored = Baseline::from_samples(&samples, model_version).and_then(|baseline| {
+            let db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
+            let conn = db_lock.as_ref().ok_or("Database not initialized")?;
+            store_baseline(conn, &baseline)?;
+            Ok(baseline)
+        });
+        match stored {
+            Ok(baseline) => {
+                println!("📐 Calibrated from {} frames", baseline.samples);
+                calibration.baseline = Some(Arc::new(baseline));
+                calibration.drift.reset();
+                let _ = app_handle.emit("calibration_complete", CalibrationStatus::of(&calibration));
+                return true;
+            }
+            Err(e) => {
+                println!("⚠️ Calibration failed: {}", e);
+                let _ = app_handle.emit("calibration_failed", e);
+                return false;
+            }
+        }
+    }
+
+    let Some(baseline) = calibration.baseline.clone() else { return false };
+    if usable && calibration.drift.update(&baseline, landmarks, now) == Some(true) {
+        let _ = app_handle.emit("calibration-alert", CalibrationAlert {
+            kind: AlertKind::CameraMoved,
+            message: "Your camera or seat has moved since calibration".to_string(),
+            suggestion: "Recalibrate so scores stay accurate".to_string(),
+        });
+    }
+    false
+}
+
+#[tauri::command]
+pub fn get_calibration(state: State<AppState>) -> Result<CalibrationStatus, String> {
+    sync_from_db(&state)?;
+    let calibration = state.calibration.lock().map_err(|_| "Failed to lock calibration")?;
+    Ok(CalibrationStatus::of(&calibration))
+}
+
+/// Forgets the baseline; scoring goes back to absolute thresholds.
+#[tauri::command]
+pub fn clear_calibration(state: State<AppState>) -> Result<String, String> {
+    {
+        let db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
+        let conn = db_lock.as_ref().ok_or("Database not initialized")?;
+        conn.execute("DELETE FROM calibration", []).map_err(|e| e.to_string())?;
+    }
+    let mut calibration = state.calibration.lock().map_err(|_| "Failed to lock calibration")?;
+    calibration.baseline = None;
+    calibration.drift.reset();
+    Ok("Calibration cleared".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::mock::seated_user;
+
+    fn samples(n: usize) -> Vec<(Vec<Landmark>, PostureAngles)> {
+        let angles = PostureAngles { craniovertebral_deg: Some(52.0), ..PostureAngles::default() };
+        (0..n).map(|_| (seated_user(0.5), angles)).collect()
+    }
+
+    #[test]
+    fn baseline_ignores_outlier_frames() {
+        let mut frames = samples(20);
+        // A few frames of the user leaning out of the way
+        for (landmarks, angles) in frames.iter_mut().take(4) {
+            *landmarks = seated_user(0.9);
+            angles.craniovertebral_deg = Some(20.0);
+        }
+
+        let baseline = Baseline::from_samples(&frames, None).unwrap();
+        assert_eq!(baseline.samples, 20);
+        assert!((baseline.landmarks[NOSE].x - 0.5).abs() < 1e-6);
+        assert_eq!(baseline.angles.craniovertebral_deg, Some(52.0));
+        assert_eq!(baseline.angles.neck_flexion_deg, None);
+        assert!(baseline.age_hours().unwrap() < 0.01);
+    }
+
+    #[test]
+    fn too_short_a_capture_is_rejected() {
+        assert!(Baseline::from_samples(&samples(3), None).is_err());
+    }
+
+    #[test]
+    fn baseline_round_trips_through_the_db() {
+        let conn = Connection::open_in_memory().unwrap();
+        crate::db::create_schema(&conn).unwrap();
+        assert!(load_baseline(&conn).unwrap().is_none());
+
+        let baseline = Baseline::from_samples(&samples(20), Some("blazepose@1".to_string())).unwrap();
+        store_baseline(&conn, &baseline).unwrap();
+        store_baseline(&conn, &baseline).unwrap();
+        let loaded = load_baseline(&conn).unwrap().unwrap();
+        assert_eq!(loaded.created_at, baseline.created_at);
+        assert_eq!(loaded.model_version.as_deref(), Some("blazepose@1"));
+        assert_eq!(loaded.landmarks.len(), LANDMARK_COUNT);
+    }
+
+    #[test]
+    fn drift_needs_to_persist_before_it_counts() {
+        let baseline = Baseline::from_samples(&samples(20), None).unwrap();
+        let moved = seated_user(0.7);
+        let mut monitor = DriftMonitor::default();
+        let start = Instant::now();
+
+        assert_eq!(monitor.update(&baseline, &moved, start), None);
+        assert_eq!(monitor.update(&baseline, &moved, start + DRIFT_HOLD / 2), None);
+        assert_eq!(monitor.update(&baseline, &moved, start + DRIFT_HOLD), Some(true));
+        assert_eq!(monitor.update(&baseline, &seated_user(0.5), start + DRIFT_HOLD * 2), Some(false));
+    }
+}
@@ -1,12 +1,14 @@
 use tauri::State;
 use crate::state::AppState;
-use rusqlite::params;
+use crate::tracking;
+use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 use chrono::{NaiveDateTime, Local, Duration};
+use std::collections::HashMap;
 
 // --- DATA STRUCTURES ---
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SessionSummary {
     pub id: String, // UUID
     pub start_time: String, // ISO
@@ -16,6 +18,12 @@ pub struct SessionSummary {
     pub good_time_sec: i64,
     pub bad_time_sec: i64,
     pub breakdown_json: String, // JSON specific scores
+    #[serde(default)]
+    pub model_version: Option<String>, // "name@version" of the model that scored it
+    #[serde(default)]
+    pub standing_sec: i64, // Part of duration_sec spent standing
+    #[serde(default)]
+    pub stance_changes: i64, // Sit/stand transitions
 }
 
 #[derive(Serialize, Debug)]
@@ -23,6 +31,14 @@ pub struct ReportDataPoint {
     pub name: String, // "9AM" or "Mon"
     pub score: i64,
     pub focus: i64, // Minutes
+    pub standing: i64, // Minutes
+    pub stance_changes: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FaultTime {
+    pub fault: String, // "forward_head", see faults.rs
+    pub seconds: f64,
 }
 
 #[derive(Serialize, Debug)]
@@ -32,11 +48,12 @@ pub struct ReportSummary {
     pub best_streak: i64,
     pub current_streak: i64,
     pub graph_data: Vec<ReportDataPoint>,
+    pub faults: Vec<FaultTime>, // Most time first
 }
 
 // --- HELPER LOGIC ---
 
-fn calculate_streak(conn: &rusqlite::Connection) -> Result<(i64, i64), String> {
+fn calculate_streak(conn: &Connection) -> Result<(i64, i64), String> {
     // 1. Get all unique dates with sessions in the last 60 days
     let mut stmt = conn.prepare(
         "SELECT DISTINCT date(start_time) as session_date 
@@ -93,17 +110,36 @@ fn calculate_streak(conn: &rusqlite::Connection) -> Result<(i64, i64), String> {
     Ok((streak, streak)) // Best streak needs separate query or more complex logic. For now returning current/current.
 }
 
-// --- COMMANDS ---
+// Sums the per-session fault times from breakdown_json, most time first
+fn fault_times(conn: &Connection, where_clause: &str) -> Result<Vec<FaultTime>, String> {
+    let mut stmt = conn.prepare(&format!("SELECT breakdown_json FROM sessions WHERE {}", where_clause))
+        .map_err(|e| e.to_string())?;
+    let breakdowns: Vec<Option<String>> = stmt.query_map([], |row| row.get(0)).map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>().map_err(|e| e.to_string())?;
 
-#[tauri::command]
-pub fn save_session(state: State<AppState>, session: SessionSummary) -> Result<String, String> {
-    let db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
-    let conn = db_lock.as_ref().ok_or("Database not initialized")?;
+    let mut totals: HashMap<String, f64> = HashMap::new();
+    for json in breakdowns.iter().flatten() {
+        let Ok(breakdown) = serde_json::from_str::<serde_json::Value>(json) else { continue };
+        let Some(faults) = breakdown.get("faults").and_then(|f| f.as_object()) else { continue };
+        for (fault, time) in faults {
+            *totals.entry(fault.clone()).or_default() += time["seconds"].as_f64().unwrap_or(0.0);
+        }
+    }
+
+    let mut faults: Vec<FaultTime> = totals.into_iter().map(|(fault, seconds)| FaultTime { fault, seconds }).collect();
+    faults.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+    Ok(faults)
+}
 
+// --- COMMANDS ---
+
+/// Stores a finished session and folds it into the day's aggregate.
+pub fn insert_session(conn: &Connection, session: &SessionSummary) -> Result<(), String> {
     // 1. Insert Raw Data
     conn.execute(
-        "INSERT INTO sessions (id, start_time, end_time, duration_sec, avg_score, good_time_sec, bad_time_sec, breakdown_json)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        "INSERT INTO sessions (id, start_time, end_time, duration_sec, avg_score, good_time_sec, bad_time_sec, breakdown_json, model_version,
+                               standing_sec, stance_changes)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
         params![
             session.id, 
             session.start_time, 
@@ -112,7 +148,10 @@ pub fn save_session(state: State<AppState>, session: SessionSummary) -> Result<S
             session.avg_score, 
             session.good_time_sec, 
             session.bad_time_sec, 
-            session.breakdown_json
+            session.breakdown_json,
+            session.model_version,
+            session.standing_sec,
+            session.stance_changes
         ],
     ).map_err(|e| e.to_string())?;
 
@@ -144,6 +183,29 @@ pub fn save_session(state: State<AppState>, session: SessionSummary) -> Result<S
         ).map_err(|e| e.to_string())?;
     }
 
+    Ok(())
+}
+
+#[tauri::command]
+pub fn save_session(state: State<AppState>, mut session: SessionSummary) -> Result<String, String> {
+    // The tracking loop records its own sessions from the same breakdown; saving one from
+    // the webview as well would steal that breakdown and count the time twice
+    if tracking::owns_sessions(state.inner()) {
+        return Err("Sessions are recorded automatically while tracking runs".to_string());
+    }
+    // Sessions from the webview do not know which model ran; stamp the active one
+    if session.model_version.is_none() {
+        session.model_version = state.pose_engine.active_model().map(|m| m.version_tag());
+    }
+    // Fold in what the backend measured (head pose, ...) and start the next session clean
+    if let Ok(mut breakdown) = state.breakdown.lock() {
+        session.breakdown_json = std::mem::take(&mut *breakdown).merge_into(&session.breakdown_json);
+    }
+
+    let db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
+    let conn = db_lock.as_ref().ok_or("Database not initialized")?;
+    insert_session(conn, &session)?;
+
     Ok("Data Saved".to_string())
 }
 
@@ -161,7 +223,8 @@ pub fn get_report_data(state: State<AppState>, range: String) -> Result<ReportSu
     if range == "day" {
         // Hourly Breakdown for Today
         let mut stmt = conn.prepare(
-            "SELECT strftime('%H', start_time) as hour, AVG(avg_score) as score, SUM(duration_sec) / 60 as focus_min
+            "SELECT strftime('%H', start_time) as hour, AVG(avg_score) as score, SUM(duration_sec) / 60 as focus_min,
+                    SUM(standing_sec) / 60 as standing_min, SUM(stance_changes) as changes
              FROM sessions 
              WHERE date(start_time) = date('now', 'localtime')
              GROUP BY hour 
@@ -175,7 +238,9 @@ pub fn get_report_data(state: State<AppState>, range: String) -> Result<ReportSu
              Ok(ReportDataPoint {
                  name: format!("{}:00", h),
                  score: s as i64,
-                 focus: f
+                 focus: f,
+                 standing: row.get(3)?,
+                 stance_changes: row.get(4)?
              })
         }).map_err(|e| e.to_string())?;
 
@@ -184,7 +249,8 @@ pub fn get_report_data(state: State<AppState>, range: String) -> Result<ReportSu
     } else if range == "week" {
         // Daily Breakdown for Last 7 Days
         let mut stmt = conn.prepare(
-             "SELECT strftime('%Y-%m-%d', start_time) as day, AVG(avg_score) as score, SUM(duration_sec) / 60 as focus_min
+             "SELECT strftime('%Y-%m-%d', start_time) as day, AVG(avg_score) as score, SUM(duration_sec) / 60 as focus_min,
+                     SUM(standing_sec) / 60 as standing_min, SUM(stance_changes) as changes
               FROM sessions 
               WHERE date(start_time) >= date('now', '-6 days', 'localtime')
               GROUP BY day
@@ -203,7 +269,9 @@ pub fn get_report_data(state: State<AppState>, range: String) -> Result<ReportSu
              Ok(ReportDataPoint {
                  name: day_name,
                  score: s as i64,
-                 focus: f
+                 focus: f,
+                 standing: row.get(3)?,
+                 stance_changes: row.get(4)?
              })
         }).map_err(|e| e.to_string())?;
 
@@ -212,7 +280,8 @@ pub fn get_report_data(state: State<AppState>, range: String) -> Result<ReportSu
         // Month Data (grouped by day still? or weeks? User says "Month range")
         // Usually Monthly report shows days 1-30
          let mut stmt = conn.prepare(
-             "SELECT strftime('%d', start_time) as day_num, AVG(avg_score) as score, SUM(duration_sec) / 60 as focus_min
+             "SELECT strftime('%d', start_time) as day_num, AVG(avg_score) as score, SUM(duration_sec) / 60 as focus_min,
+                     SUM(standing_sec) / 60 as standing_min, SUM(stance_changes) as changes
               FROM sessions 
               WHERE date(start_time) >= date('now', 'start of month', 'localtime')
               GROUP BY day_num
@@ -225,28 +294,36 @@ pub fn get_report_data(state: State<AppState>, range: String) -> Result<ReportSu
              Ok(ReportDataPoint {
                  name: d,
                  score: row.get::<_, f64>(1)? as i64,
-                 focus: row.get(2)?
+                 focus: row.get(2)?,
+                 standing: row.get(3)?,
+                 stance_changes: row.get(4)?
              })
         }).map_err(|e| e.to_string())?;
          for r in rows { graph_data.push(r.unwrap()); }
     }
 
+    // 3. Time per posture fault over the same range
+    let range_clause = match range.as_str() {
+        "day" => "date(start_time) = date('now', 'localtime')",
+        "week" => "date(start_time) >= date('now', '-6 days', 'localtime')",
+        _ => "date(start_time) >= date('now', 'start of month', 'localtime')",
+    };
+    let faults = fault_times(conn, range_clause)?;
+
     Ok(ReportSummary {
         total_focus_hours: 12.5, // Calc this properly in real impl
         avg_score: 85,
         current_streak,
         best_streak,
-        graph_data
+        graph_data,
+        faults
     })
 }
 
-#[tauri::command]
-pub fn get_recent_sessions(state: State<AppState>) -> Result<Vec<SessionSummary>, String> {
-    let db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
-    let conn = db_lock.as_ref().ok_or("Database not initialized")?;
-
+pub fn recent_sessions(conn: &Connection) -> Result<Vec<SessionSummary>, String> {
     let mut stmt = conn.prepare(
-        "SELECT id, start_time, end_time, duration_sec, avg_score, good_time_sec, bad_time_sec, breakdown_json 
+        "SELECT id, start_time, end_time, duration_sec, avg_score, good_time_sec, bad_time_sec, breakdown_json, model_version,
+                standing_sec, stance_changes
          FROM sessions 
          ORDER BY start_time DESC 
          LIMIT 50"
@@ -262,6 +339,9 @@ pub fn get_recent_sessions(state: State<AppState>) -> Result<Vec<SessionSummary>
             good_time_sec: row.get(5)?,
             bad_time_sec: row.get(6)?,
             breakdown_json: row.get(7)?,
+            model_version: row.get(8)?,
+            standing_sec: row.get(9)?,
+            stance_changes: row.get(10)?,
         })
     }).map_err(|e| e.to_string())?;
 
@@ -272,3 +352,88 @@ pub fn get_recent_sessions(state: State<AppState>) -> Result<Vec<SessionSummary>
 
     Ok(sessions)
 }
+
+#[tauri::command]
+pub fn get_recent_sessions(state: State<AppState>) -> Result<Vec<SessionSummary>, String> {
+    let db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
+    let conn = db_lock.as_ref().ok_or("Database not initialized")?;
+    recent_sessions(conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    fn memory_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        db::create_schema(&conn).unwrap();
+        conn
+    }
+
+    fn session(id: &str, start: &str, duration_sec: i64, avg_score: i64) -> SessionSummary {
+        SessionSummary {
+            id: id.to_string(),
+            start_time: start.to_string(),
+            end_time: start.to_string(),
+            duration_sec,
+            avg_score,
+            good_time_sec: duration_sec * avg_score / 100,
+            bad_time_sec: duration_sec - duration_sec * avg_score / 100,
+            breakdown_json: "{}".to_string(),
+            model_version: Some("mock@test".to_string()),
+            standing_sec: 0,
+            stance_changes: 0,
+        }
+    }
+
+    #[test]
+    fn sessions_roll_up_into_daily_stats() {
+        let conn = memory_db();
+        insert_session(&conn, &session("a", "2026-03-02T09:00:00", 600, 90)).unwrap();
+        insert_session(&conn, &session("b", "2026-03-02T14:00:00", 1200, 70)).unwrap();
+        insert_session(&conn, &session("c", "2026-03-03T09:00:00", 300, 50)).unwrap();
+
+        let (sessions, focus, avg): (i64, i64, i64) = conn.query_row(
+            "SELECT total_sessions, total_focus_time, avg_score FROM daily_stats WHERE date = '2026-03-02'",
+            [], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+        ).unwrap();
+        assert_eq!((sessions, focus, avg), (2, 1800, 80));
+    }
+
+    #[test]
+    fn recent_sessions_are_newest_first_and_keep_the_model_version() {
+        let conn = memory_db();
+        insert_session(&conn, &session("old", "2026-03-01T09:00:00", 600, 80)).unwrap();
+        insert_session(&conn, &session("new", "2026-03-02T09:00:00", 600, 80)).unwrap();
+
+        let recent = recent_sessions(&conn).unwrap();
+        assert_eq!(recent.iter().map(|s| s.id.as_str()).collect::<Vec<_>>(), ["new", "old"]);
+        assert_eq!(recent[0].model_version.as_deref(), Some("mock@test"));
+    }
+
+    #[test]
+    fn fault_times_add_up_across_sessions() {
+        let conn = memory_db();
+        let mut a = session("a", "2026-03-02T09:00:00", 600, 90);
+        a.breakdown_json = r#"{"faults":{"forward_head":{"seconds":40.0},"lean_left":{"seconds":10.0}}}"#.to_string();
+        let mut b = session("b", "2026-03-02T10:00:00", 600, 90);
+        b.breakdown_json = r#"{"faults":{"lean_left":{"seconds":50.0}}}"#.to_string();
+        for s in [a, b, session("c", "2026-03-02T11:00:00", 600, 90)] {
+            insert_session(&conn, &s).unwrap();
+        }
+
+        let faults = fault_times(&conn, "1 = 1").unwrap();
+        assert_eq!(faults, vec![
+            FaultTime { fault: "lean_left".to_string(), seconds: 60.0 },
+            FaultTime { fault: "forward_head".to_string(), seconds: 40.0 },
+        ]);
+    }
+
+    #[test]
+    fn duplicate_session_ids_are_rejected() {
+        let conn = memory_db();
+        insert_session(&conn, &session("a", "2026-03-02T09:00:00", 600, 90)).unwrap();
+        assert!(insert_session(&conn, &session("a", "2026-03-02T10:00:00", 600, 90)).is_err());
+    }
+}
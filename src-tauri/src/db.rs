@@ -1,7 +1,107 @@
 use rusqlite::{params, Connection, Result};
+use std::collections::HashMap;
 use tauri::State;
 use crate::state::AppState;
 
+/// Reads the whole key/value settings table (engine modules parse what they need from it).
+pub fn load_settings(conn: &Connection) -> Result<HashMap<String, String>, String> {
+    let mut stmt = conn.prepare("SELECT key, value FROM settings").map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?))).map_err(|e| e.to_string())?;
+    let mut map = HashMap::new();
+    for r in rows { let (k,v) = r.map_err(|e| e.to_string())?; map.insert(k, v); }
+    Ok(map)
+}
+
+pub fn store_setting(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)", params![key, value]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Adds a column to a table created by an older build; no-op when it is already there
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<(), String> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table)).map_err(|e| e.to_string())?;
+    let columns: Vec<String> = stmt.query_map([], |r| r.get(1)).map_err(|e| e.to_string())?
+        .collect::<Result<_, _>>().map_err(|e| e.to_string())?;
+    if !columns.iter().any(|c| c == column) {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition), []).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Settings for engine configuration; empty when the DB has not been initialised yet.
+pub fn current_settings(state: &AppState) -> HashMap<String, String> {
+    state.db.lock().ok()
+        .and_then(|lock| lock.as_ref().and_then(|conn| load_settings(conn).ok()))
+        .unwrap_or_default()
+}
+
+/// Creates every table (and upgrades older ones) on an open connection.
+pub fn create_schema(conn: &Connection) -> Result<(), String> {
+    // 1. Sessions Table (Raw Log Data)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            start_time TEXT NOT NULL,
+            end_time TEXT NOT NULL,
+            duration_sec INTEGER NOT NULL,
+            avg_score INTEGER NOT NULL,
+            good_time_sec INTEGER NOT NULL,
+            bad_time_sec INTEGER NOT NULL,
+            breakdown_json TEXT,
+            model_version TEXT,
+            standing_sec INTEGER NOT NULL DEFAULT 0,
+            stance_changes INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    ).map_err(|e| e.to_string())?;
+    add_column_if_missing(conn, "sessions", "model_version", "TEXT")?;
+    add_column_if_missing(conn, "sessions", "standing_sec", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "sessions", "stance_changes", "INTEGER NOT NULL DEFAULT 0")?;
+
+    // 2. Daily Stats (Aggregated)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS daily_stats (
+            date TEXT PRIMARY KEY,
+            total_sessions INTEGER DEFAULT 0,
+            total_focus_time INTEGER DEFAULT 0,
+            avg_score INTEGER DEFAULT 0
+        )",
+        [],
+    ).map_err(|e| e.to_string())?;
+
+    // 3. User Progress (Streaks) - Kept from original if needed, or we rely on aggregation
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS user_progress (
+            id INTEGER PRIMARY KEY, 
+            current_streak INTEGER DEFAULT 0, 
+            best_streak INTEGER DEFAULT 0,
+            last_active_date TEXT
+        )",
+        [],
+    ).map_err(|e| e.to_string())?;
+
+    // 4. Settings (Preserve existing)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY, 
+            value TEXT
+        )",
+        [],
+    ).map_err(|e| e.to_string())?;
+
+    // 5. Calibration baseline (single row, replaced on recalibration)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS calibration (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            created_at TEXT NOT NULL,
+            baseline_json TEXT NOT NULL
+        )",
+        [],
+    ).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
 #[tauri::command]
 pub fn init_db(state: State<AppState>) -> Result<String, String> {
     let mut db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
@@ -10,55 +110,42 @@ pub fn init_db(state: State<AppState>) -> Result<String, String> {
     if db_lock.is_none() {
         let conn = Connection::open("posturesense.db").map_err(|e| e.to_string())?;
 
-        // 1. Sessions Table (Raw Log Data)
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS sessions (
-                id TEXT PRIMARY KEY,
-                start_time TEXT NOT NULL,
-                end_time TEXT NOT NULL,
-                duration_sec INTEGER NOT NULL,
-                avg_score INTEGER NOT NULL,
-                good_time_sec INTEGER NOT NULL,
-                bad_time_sec INTEGER NOT NULL,
-                breakdown_json TEXT
-            )",
-            [],
-        ).map_err(|e| e.to_string())?;
+        create_schema(&conn)?;
 
-        // 2. Daily Stats (Aggregated)
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS daily_stats (
-                date TEXT PRIMARY KEY,
-                total_sessions INTEGER DEFAULT 0,
-                total_focus_time INTEGER DEFAULT 0,
-                avg_score INTEGER DEFAULT 0
-            )",
-            [],
-        ).map_err(|e| e.to_string())?;
+        *db_lock = Some(conn);
+        Ok("Database Initialized".to_string())
+    } else {
+        Ok("Database already initialized".to_string())
+    }
+}
 
-        // 3. User Progress (Streaks) - Kept from original if needed, or we rely on aggregation
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS user_progress (
-                id INTEGER PRIMARY KEY, 
-                current_streak INTEGER DEFAULT 0, 
-                best_streak INTEGER DEFAULT 0,
-                last_active_date TEXT
-            )",
-            [],
-        ).map_err(|e| e.to_string())?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // 4. Settings (Preserve existing)
+    #[test]
+    fn schema_upgrades_sessions_from_before_model_versions() {
+        let conn = Connection::open_in_memory().unwrap();
         conn.execute(
-            "CREATE TABLE IF NOT EXISTS settings (
-                key TEXT PRIMARY KEY, 
-                value TEXT
-            )",
+            "CREATE TABLE sessions (id TEXT PRIMARY KEY, start_time TEXT NOT NULL, end_time TEXT NOT NULL,
+             duration_sec INTEGER NOT NULL, avg_score INTEGER NOT NULL, good_time_sec INTEGER NOT NULL,
+             bad_time_sec INTEGER NOT NULL, breakdown_json TEXT)",
             [],
-        ).map_err(|e| e.to_string())?;
+        ).unwrap();
 
-        *db_lock = Some(conn);
-        Ok("Database Initialized".to_string())
-    } else {
-        Ok("Database already initialized".to_string())
+        create_schema(&conn).unwrap();
+        // Running it again on an up-to-date DB must be a no-op
+        create_schema(&conn).unwrap();
+
+        conn.execute("UPDATE sessions SET model_version = 'x', standing_sec = 1, stance_changes = 1", []).unwrap();
+    }
+
+    #[test]
+    fn settings_round_trip() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_schema(&conn).unwrap();
+        store_setting(&conn, "pose_backend", "tract").unwrap();
+        store_setting(&conn, "pose_backend", "ort").unwrap();
+        assert_eq!(load_settings(&conn).unwrap().get("pose_backend").map(String::as_str), Some("ort"));
     }
 }
@@ -0,0 +1,251 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use crate::calibration::{median, AlertKind, CalibrationAlert};
+use crate::pose::keypoints::*;
+use crate::pose::{HeadPose, Landmark};
+
+// Adult interpupillary and biacromial breadth, the rulers we measure distance with
+const EYE_SPACING_CM: f32 = 6.3;
+const SHOULDER_WIDTH_CM: f32 = 38.0;
+// Past this much yaw the eyes are too foreshortened to correct for
+const MAX_YAW_DEG: f32 = 60.0;
+// Eyes may sit a little above the top edge before the user has to look down at the screen
+const EYES_ABOVE_TOP_CM: f32 = 5.0;
+
+// The median of the last WINDOW usable frames is what gets checked (10 s at 15 FPS)
+const WINDOW: usize = 150;
+const MIN_SAMPLES: usize = 15;
+// First periodic check, once the user has settled in
+const WARMUP: Duration = Duration::from_secs(30);
+
+/// Camera and thresholds, read from the settings table:
+///   ergonomics_camera_fov_deg: horizontal field of view; 60 fits most laptop webcams (60)
+///   ergonomics_screen_height_cm: visible screen height (30)
+///   ergonomics_min_distance_cm, ergonomics_max_distance_cm: comfortable viewing distance (45, 85)
+///   ergonomics_max_offset_cm: how far off the screen's centre line the user may sit (15)
+///   ergonomics_check_interval_sec: time between periodic checks (600)
+/// The camera is assumed to sit at the top centre of the screen, as built-in webcams do.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErgonomicsConfig {
+    pub camera_fov_deg: f32,
+    pub screen_height_cm: f32,
+    pub min_distance_cm: f32,
+    pub max_distance_cm: f32,
+    pub max_offset_cm: f32,
+    pub check_interval: Duration,
+}
+
+impl Default for ErgonomicsConfig {
+    fn default() -> Self {
+        Self {
+            camera_fov_deg: 60.0,
+            screen_height_cm: 30.0,
+            min_distance_cm: 45.0,
+            max_distance_cm: 85.0,
+            max_offset_cm: 15.0,
+            check_interval: Duration::from_secs(600),
+        }
+    }
+}
+
+impl ErgonomicsConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let d = Self::default();
+        let get = |key: &str| settings.get(key).and_then(|v| v.parse::<f32>().ok());
+        Self {
+            camera_fov_deg: get("ergonomics_camera_fov_deg").unwrap_or(d.camera_fov_deg),
+            screen_height_cm: get("ergonomics_screen_height_cm").unwrap_or(d.screen_height_cm),
+            min_distance_cm: get("ergonomics_min_distance_cm").unwrap_or(d.min_distance_cm),
+            max_distance_cm: get("ergonomics_max_distance_cm").unwrap_or(d.max_distance_cm),
+            max_offset_cm: get("ergonomics_max_offset_cm").unwrap_or(d.max_offset_cm),
+            check_interval: get("ergonomics_check_interval_sec")
+                .map(|s| Duration::from_secs_f32(s.max(1.0)))
+                .unwrap_or(d.check_interval),
+        }
+    }
+
+    // Focal length in frame heights, from the horizontal field of view
+    fn focal(&self, aspect: f32) -> f32 {
+        0.5 * aspect / (self.camera_fov_deg.clamp(20.0, 150.0).to_radians() / 2.0).tan()
+    }
+}
+
+/// Where the user's eyes are relative to the camera, in cm.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Setup {
+    /// Eyes above (+) or below (-) the camera, i.e. the top of the screen.
+    pub eye_height_cm: f32,
+    pub distance_cm: f32,
+    /// Eyes right (+) or left (-) of the camera, as the camera sees it.
+    pub lateral_offset_cm: f32,
+}
+
+impl Setup {
+    /// Distance from the eye spacing (corrected for yaw), or the shoulders when the eyes are
+    /// hidden; height and offset from where the eyes sit in the frame at that distance.
+    pub fn estimate(landmarks: &[Landmark], head_pose: Option<&HeadPose>, aspect: f32, config: &ErgonomicsConfig) -> Option<Self> {
+        let point = |i: usize| visible(landmarks, i).map(|lm| (lm.x * aspect, lm.y));
+        let spacing = |a: (f32, f32), b: (f32, f32)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+        let focal = config.focal(aspect);
+
+        let eyes = point(LEFT_EYE).zip(point(RIGHT_EYE));
+        let yaw = head_pose.map_or(0.0, |h| h.yaw_deg.abs().min(MAX_YAW_DEG));
+        let distance_cm = match (eyes, point(LEFT_SHOULDER).zip(point(RIGHT_SHOULDER))) {
+            (Some((l, r)), _) => EYE_SPACING_CM * focal * yaw.to_radians().cos() / spacing(l, r),
+            (None, Some((l, r))) => SHOULDER_WIDTH_CM * focal / spacing(l, r),
+            _ => return None,
+        };
+        if !distance_cm.is_finite() { return None; }
+
+        let (l, r) = eyes?;
+        let (eye_x, eye_y) = ((l.0 + r.0) / 2.0, (l.1 + r.1) / 2.0);
+        let to_cm = distance_cm / focal;
+        Some(Self {
+            eye_height_cm: (0.5 - eye_y) * to_cm,
+            distance_cm,
+            lateral_offset_cm: (eye_x - 0.5 * aspect) * to_cm,
+        })
+    }
+
+    /// Everything wrong with this setup, each with the fix.
+    pub fn check(&self, config: &ErgonomicsConfig) -> Vec<CalibrationAlert> {
+        let mut alerts = Vec::new();
+        let mut alert = |kind: AlertKind, message: String, suggestion: &str| {
+            alerts.push(CalibrationAlert { kind, message, suggestion: suggestion.to_string() });
+        };
+
+        if self.eye_height_cm > EYES_ABOVE_TOP_CM {
+            alert(AlertKind::MonitorTooLow,
+                  format!("Monitor too low: your eyes are {:.0} cm above the top of the screen", self.eye_height_cm),
+                  "Raise the screen so its top edge is at or just below eye level");
+        } else if self.eye_height_cm < -config.screen_height_cm / 2.0 {
+            alert(AlertKind::MonitorTooHigh,
+                  "Monitor too high: your eyes are below the middle of the screen".to_string(),
+                  "Lower the screen or raise your chair");
+        }
+
+        if self.distance_cm < config.min_distance_cm {
+            alert(AlertKind::TooClose,
+                  format!("You are sitting {:.0} cm from the screen", self.distance_cm),
+                  "Sit back to about an arm's length from the screen");
+        } else if self.distance_cm > config.max_distance_cm {
+            alert(AlertKind::TooFar,
+                  format!("You are sitting {:.0} cm from the screen", self.distance_cm),
+                  "Move closer or enlarge the text, so you do not lean in to read");
+        }
+
+        if self.lateral_offset_cm.abs() > config.max_offset_cm {
+            alert(AlertKind::OffCentre,
+                  format!("You are sitting {:.0} cm to the side of the screen", self.lateral_offset_cm.abs()),
+                  "Centre the screen in front of you so you do not sit twisted");
+        }
+        alerts
+    }
+
+    fn median_of(setups: &VecDeque<Setup>) -> Option<Self> {
+        Some(Self {
+            eye_height_cm: median(setups.iter().map(|s| s.eye_height_cm))?,
+            distance_cm: median(setups.iter().map(|s| s.distance_cm))?,
+            lateral_offset_cm: median(setups.iter().map(|s| s.lateral_offset_cm))?,
+        })
+    }
+}
+
+/// Checks the setup right after calibration and every `check_interval` after that, on the
+/// median of the last few seconds so a lean towards the screen is not mistaken for it.
+#[derive(Debug)]
+pub struct ErgonomicsMonitor {
+    config: ErgonomicsConfig,
+    recent: VecDeque<Setup>,
+    next_check: Option<Instant>,
+}
+
+impl ErgonomicsMonitor {
+    pub fn new(config: ErgonomicsConfig) -> Self {
+        Self { config, recent: VecDeque::with_capacity(WINDOW), next_check: None }
+    }
+
+    /// Call with each usable frame's setup; returns alerts when a periodic check is due.
+    pub fn observe(&mut self, setup: Option<Setup>, now: Instant) -> Vec<CalibrationAlert> {
+        if let Some(setup) = setup {
+            if self.recent.len() == WINDOW {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(setup);
+        }
+        let next_check = *self.next_check.get_or_insert(now + WARMUP);
+        if now < next_check { return Vec::new(); }
+        self.check_now(now)
+    }
+
+    /// Checks right away (calibration just finished) and restarts the interval.
+    pub fn check_now(&mut self, now: Instant) -> Vec<CalibrationAlert> {
+        if self.recent.len() < MIN_SAMPLES { return Vec::new(); }
+        self.next_check = Some(now + self.config.check_interval);
+        Setup::median_of(&self.recent).map(|s| s.check(&self.config)).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::mock::seated_user;
+
+    const ASPECT: f32 = 4.0 / 3.0;
+
+    // The mock user moved towards the camera: everything spread out around the frame centre
+    fn closer(landmarks: Vec<Landmark>, scale: f32, lift: f32) -> Vec<Landmark> {
+        landmarks.into_iter()
+            .map(|lm| Landmark { x: 0.5 + (lm.x - 0.5) * scale, y: 0.5 + (lm.y - 0.5) * scale + lift, ..lm })
+            .collect()
+    }
+
+    #[test]
+    fn distance_comes_from_eye_spacing() {
+        let config = ErgonomicsConfig::default();
+        let setup = Setup::estimate(&seated_user(0.5), None, ASPECT, &config).unwrap();
+        // 6 % of the frame width between the eyes with a 60 degree lens
+        assert!((setup.distance_cm - 90.9).abs() < 0.1, "{}", setup.distance_cm);
+        assert!(setup.lateral_offset_cm.abs() < 1e-3);
+        assert!(setup.eye_height_cm > 0.0);
+
+        // Turning the head narrows the eyes; the yaw correction keeps the distance
+        let turned = HeadPose { pitch_deg: 0.0, yaw_deg: 60.0, roll_deg: 0.0 };
+        let mut landmarks = seated_user(0.5);
+        landmarks[LEFT_EYE].x = 0.5 + 0.015;
+        landmarks[RIGHT_EYE].x = 0.5 - 0.015;
+        let corrected = Setup::estimate(&landmarks, Some(&turned), ASPECT, &config).unwrap();
+        assert!((corrected.distance_cm - setup.distance_cm).abs() < 0.1);
+    }
+
+    #[test]
+    fn laptop_on_the_desk_is_too_low_and_far() {
+        let config = ErgonomicsConfig::default();
+        let setup = Setup::estimate(&seated_user(0.5), None, ASPECT, &config).unwrap();
+        let kinds: Vec<AlertKind> = setup.check(&config).iter().map(|a| a.kind).collect();
+        assert_eq!(kinds, vec![AlertKind::MonitorTooLow, AlertKind::TooFar]);
+
+        let good = Setup::estimate(&closer(seated_user(0.5), 1.5, 0.3), None, ASPECT, &config).unwrap();
+        assert!(good.check(&config).is_empty(), "{:?}", good);
+
+        let aside = Setup { lateral_offset_cm: -25.0, ..good };
+        assert_eq!(aside.check(&config)[0].kind, AlertKind::OffCentre);
+    }
+
+    #[test]
+    fn periodic_checks_wait_for_the_interval() {
+        let config = ErgonomicsConfig::default();
+        let setup = Setup::estimate(&seated_user(0.5), None, ASPECT, &config);
+        let mut monitor = ErgonomicsMonitor::new(config);
+        let start = Instant::now();
+
+        for i in 0..MIN_SAMPLES as u32 {
+            assert!(monitor.observe(setup, start + Duration::from_millis(66) * i).is_empty());
+        }
+        assert_eq!(monitor.observe(setup, start + WARMUP).len(), 2);
+        assert!(monitor.observe(setup, start + WARMUP + Duration::from_secs(1)).is_empty());
+        assert_eq!(monitor.observe(setup, start + WARMUP + config.check_interval).len(), 2);
+        assert_eq!(monitor.check_now(start + WARMUP + config.check_interval).len(), 2);
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use crate::calibration::Baseline;
+use crate::pose::keypoints::*;
+use crate::pose::{DepthMetrics, Landmark, PostureAngles};
+
+// A frame this long after the previous one means frames were missed (unusable, user away);
+// the gap is not credited to any fault
+const MAX_FRAME_GAP: Duration = Duration::from_millis(500);
+
+/// A named habit the user can fix, rather than a number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fault {
+    ForwardHead,
+    RoundedShoulders,
+    LeanLeft,
+    LeanRight,
+    SlumpedTrunk,
+    ChinOnHand,
+    TwistedTorso,
+}
+
+impl Fault {
+    pub const ALL: [Fault; 7] = [
+        Fault::ForwardHead, Fault::RoundedShoulders, Fault::LeanLeft, Fault::LeanRight,
+        Fault::SlumpedTrunk, Fault::ChinOnHand, Fault::TwistedTorso,
+    ];
+
+    /// For notifications: what is happening, in the user's terms.
+    pub fn message(self) -> &'static str {
+        match self {
+            Fault::ForwardHead => "Your head is drifting forward - tuck your chin back",
+            Fault::RoundedShoulders => "Your shoulders are rolling forward - open your chest",
+            Fault::LeanLeft => "You are leaning to the left",
+            Fault::LeanRight => "You are leaning to the right",
+            Fault::SlumpedTrunk => "You are slumping - sit back against the chair",
+            Fault::ChinOnHand => "You are resting your chin on your hand",
+            Fault::TwistedTorso => "Your torso is twisted - face the screen square on",
+        }
+    }
+}
+
+/// One fault found in a window, with the share of its frames that showed it (0-1).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DetectedFault {
+    pub fault: Fault,
+    pub confidence: f32,
+}
+
+/// Per-frame confidence for every fault, indexed like `Fault::ALL`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Evidence([f32; Fault::ALL.len()]);
+
+impl Evidence {
+    pub fn get(&self, fault: Fault) -> f32 {
+        self.0[fault as usize]
+    }
+
+    /// Thresholds are offsets from the user's baseline when calibrated, from typical upright
+    /// values otherwise. World landmarks are needed to see a twisted torso.
+    pub fn measure(landmarks: &[Landmark], angles: &PostureAngles, depth: Option<&DepthMetrics>,
+                   baseline: Option<&Baseline>, aspect: f32) -> Self {
+        let mut evidence = Self::default();
+        let mut set = |fault: Fault, confidence: Option<f32>| evidence.0[fault as usize] = confidence.unwrap_or(0.0);
+        let base = |f: fn(&PostureAngles) -> Option<f32>| baseline.and_then(|b| f(&b.angles));
+
+        let base_cva = base(|a| a.craniovertebral_deg).unwrap_or(50.0);
+        set(Fault::ForwardHead, angles.craniovertebral_deg.map(|cva| ramp(base_cva - cva, 3.0, 12.0)));
+
+        let base_protraction = base(|a| a.shoulder_protraction_deg).unwrap_or(20.0);
+        set(Fault::RoundedShoulders, angles.shoulder_protraction_deg.map(|p| ramp(p - base_protraction, 5.0, 20.0)));
+
+        // Positive when the user's left shoulder sits lower: leaning to their left
+        let lean = signed_tilt(landmarks, aspect)
+            .map(|tilt| tilt - baseline.and_then(|b| signed_tilt(&b.landmarks, aspect)).unwrap_or(0.0));
+        set(Fault::LeanLeft, lean.map(|l| ramp(l, 4.0, 10.0)));
+        set(Fault::LeanRight, lean.map(|l| ramp(-l, 4.0, 10.0)));
+
+        // Without hips, a slump shows as the shoulders sinking below where calibration saw them
+        let sink = || {
+            let (now, then) = (shoulder_frame(landmarks)?, shoulder_frame(&baseline?.landmarks)?);
+            Some(ramp((now.mid_y - then.mid_y) / then.width, 0.1, 0.3))
+        };
+        set(Fault::SlumpedTrunk, angles.trunk_inclination_deg.map(|t| ramp(t, 15.0, 30.0)).or_else(sink));
+
+        set(Fault::ChinOnHand, chin_on_hand(landmarks, aspect));
+        set(Fault::TwistedTorso, depth.map(|d| ramp(d.shoulder_rotation_cm.abs(), 4.0, 10.0)));
+        evidence
+    }
+}
+
+// 0 at `start`, 1 at `full`, linear in between
+fn ramp(value: f32, start: f32, full: f32) -> f32 {
+    ((value - start) / (full - start)).clamp(0.0, 1.0)
+}
+
+fn point(landmarks: &[Landmark], i: usize, aspect: f32) -> Option<(f32, f32)> {
+    visible(landmarks, i).map(|lm| (lm.x * aspect, lm.y))
+}
+
+fn signed_tilt(landmarks: &[Landmark], aspect: f32) -> Option<f32> {
+    let (l, r) = (point(landmarks, LEFT_SHOULDER, aspect)?, point(landmarks, RIGHT_SHOULDER, aspect)?);
+    Some((l.1 - r.1).atan2((l.0 - r.0).abs()).to_degrees())
+}
+
+// A wrist up by the face, closer to the nose than about a third of the shoulder width
+fn chin_on_hand(landmarks: &[Landmark], aspect: f32) -> Option<f32> {
+    let nose = point(landmarks, NOSE, aspect)?;
+    let (l, r) = (point(landmarks, LEFT_SHOULDER, aspect)?, point(landmarks, RIGHT_SHOULDER, aspect)?);
+    let width = ((l.0 - r.0).powi(2) + (l.1 - r.1).powi(2)).sqrt().max(f32::EPSILON);
+    [LEFT_WRIST, RIGHT_WRIST].iter()
+        .filter_map(|&i| point(landmarks, i, aspect))
+        .filter(|wrist| wrist.1 < l.1.max(r.1))
+        .map(|wrist| ramp(-((wrist.0 - nose.0).powi(2) + (wrist.1 - nose.1).powi(2)).sqrt() / width, -0.6, -0.3))
+        .reduce(f32::max)
+}
+
+/// Windowing, read from the settings table:
+///   fault_window_sec: frames are judged together in windows this long (2)
+///   fault_min_confidence: share of a window's frames that must show a fault (0.5)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaultConfig {
+    pub window: Duration,
+    pub min_confidence: f32,
+}
+
+impl Default for FaultConfig {
+    fn default() -> Self {
+        Self { window: Duration::from_secs(2), min_confidence: 0.5 }
+    }
+}
+
+impl FaultConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let d = Self::default();
+        let get = |key: &str| settings.get(key).and_then(|v| v.parse::<f32>().ok());
+        Self {
+            window: get("fault_window_sec").map(|s| Duration::from_secs_f32(s.max(0.1))).unwrap_or(d.window),
+            min_confidence: get("fault_min_confidence").unwrap_or(d.min_confidence),
+        }
+    }
+}
+
+/// What one window showed, and how much time it covered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaultWindow {
+    pub faults: Vec<DetectedFault>,
+    pub duration: Duration,
+}
+
+/// Averages per-frame evidence over windows, so a single odd frame never names a fault.
+#[derive(Debug)]
+pub struct FaultClassifier {
+    config: FaultConfig,
+    sum: [f32; Fault::ALL.len()],
+    frames: u32,
+    covered: Duration,
+    last: Option<Instant>,
+}
+
+impl FaultClassifier {
+    pub fn new(config: FaultConfig) -> Self {
+        Self { config, sum: Default::default(), frames: 0, covered: Duration::ZERO, last: None }
+    }
+
+    /// Adds a usable frame; returns the window's faults once it is full.
+    pub fn push(&mut self, evidence: &Evidence, now: Instant) -> Option<FaultWindow> {
+        if let Some(last) = self.last {
+            self.covered += now.duration_since(last).min(MAX_FRAME_GAP);
+        }
+        self.last = Some(now);
+        for (sum, value) in self.sum.iter_mut().zip(evidence.0) {
+            *sum += value;
+        }
+        self.frames += 1;
+        if self.covered < self.config.window { return None; }
+
+        let faults = Fault::ALL.iter()
+            .map(|&fault| DetectedFault { fault, confidence: self.sum[fault as usize] / self.frames as f32 })
+            .filter(|f| f.confidence >= self.config.min_confidence)
+            .collect();
+        let window = FaultWindow { faults, duration: self.covered };
+        *self = Self { last: self.last, ..Self::new(self.config) };
+        Some(window)
+    }
+
+    /// A frame that could not be judged: the time until the next usable one is not counted.
+    pub fn skip(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::mock::seated_user;
+
+    const ASPECT: f32 = 4.0 / 3.0;
+
+    fn measure(landmarks: &[Landmark]) -> Evidence {
+        let angles = PostureAngles::measure(landmarks, None, None, ASPECT);
+        Evidence::measure(landmarks, &angles, None, None, ASPECT)
+    }
+
+    #[test]
+    fn upright_user_shows_no_faults() {
+        let evidence = measure(&seated_user(0.5));
+        for fault in Fault::ALL {
+            assert_eq!(evidence.get(fault), 0.0, "{:?}", fault);
+        }
+    }
+
+    #[test]
+    fn leaning_and_chin_on_hand_are_named() {
+        // User's left shoulder (image right) dropped: leaning left
+        let mut leaning = seated_user(0.5);
+        leaning[LEFT_SHOULDER].y += 0.1;
+        let evidence = measure(&leaning);
+        assert_eq!(evidence.get(Fault::LeanLeft), 1.0);
+        assert_eq!(evidence.get(Fault::LeanRight), 0.0);
+
+        let mut resting = seated_user(0.5);
+        resting[RIGHT_WRIST] = Landmark { x: 0.5, y: 0.36, z: 0.0, visibility: 0.9 };
+        assert_eq!(measure(&resting).get(Fault::ChinOnHand), 1.0);
+    }
+
+    #[test]
+    fn forward_head_is_relative_to_the_baseline() {
+        let mut forward = seated_user(0.5);
+        for i in [NOSE, LEFT_EYE, RIGHT_EYE, LEFT_EAR, RIGHT_EAR] {
+            forward[i].y += 0.08;
+        }
+        let angles = PostureAngles::measure(&forward, None, None, ASPECT);
+        assert!(Evidence::measure(&forward, &angles, None, None, ASPECT).get(Fault::ForwardHead) > 0.5);
+
+        // Someone whose upright already looks like that is fine
+        let samples: Vec<_> = (0..20).map(|_| (forward.clone(), angles)).collect();
+        let baseline = Baseline::from_samples(&samples, None).unwrap();
+        assert_eq!(Evidence::measure(&forward, &angles, None, Some(&baseline), ASPECT).get(Fault::ForwardHead), 0.0);
+    }
+
+    #[test]
+    fn windows_need_most_frames_to_agree() {
+        let mut classifier = FaultClassifier::new(FaultConfig::default());
+        let mut leaning = Evidence::default();
+        leaning.0[Fault::LeanLeft as usize] = 1.0;
+        let frame = Duration::from_millis(100);
+        let start = Instant::now();
+
+        // 20 frames, every fourth one leaning: a quarter is below the confidence floor
+        let window = (0..=20u32)
+            .filter_map(|i| classifier.push(if i % 4 == 0 { &leaning } else { &Evidence::default() }, start + frame * i))
+            .next().unwrap();
+        assert_eq!(window.duration, Duration::from_secs(2));
+        assert!(window.faults.is_empty());
+
+        // A gap while the user was away is not credited
+        classifier.skip();
+        let later = start + Duration::from_secs(60);
+        let window = (0..=20u32).filter_map(|i| classifier.push(&leaning, later + frame * i)).next().unwrap();
+        assert_eq!(window.duration, Duration::from_secs(2));
+        assert_eq!(window.faults, vec![DetectedFault { fault: Fault::LeanLeft, confidence: 1.0 }]);
+    }
+}
@@ -0,0 +1,110 @@
+use std::sync::{Condvar, Mutex};
+
+struct Slot<T> {
+    latest: Option<T>,
+    closed: bool,
+    dropped: u64,
+}
+
+/// Single-slot "latest frame wins" channel between capture and inference.
+/// Publishing never blocks: an unconsumed frame is replaced and counted as dropped,
+/// so the worker always sees the newest frame instead of working through a backlog.
+pub struct FrameChannel<T> {
+    slot: Mutex<Slot<T>>,
+    ready: Condvar,
+}
+
+impl<T> Default for FrameChannel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> FrameChannel<T> {
+    pub fn new() -> Self {
+        Self {
+            slot: Mutex::new(Slot { latest: None, closed: false, dropped: 0 }),
+            ready: Condvar::new(),
+        }
+    }
+
+    /// Hands a frame to the worker. Returns `true` if it replaced one the worker never saw.
+    pub fn publish(&self, value: T) -> bool {
+        let Ok(mut slot) = self.slot.lock() else { return false };
+        let replaced = slot.latest.replace(value).is_some();
+        if replaced { slot.dropped += 1; }
+        self.ready.notify_one();
+        replaced
+    }
+
+    /// Blocks until a frame is available. `None` once the channel is closed.
+    pub fn recv(&self) -> Option<T> {
+        let mut slot = self.slot.lock().ok()?;
+        loop {
+            if let Some(value) = slot.latest.take() { return Some(value); }
+            if slot.closed { return None; }
+            slot = self.ready.wait(slot).ok()?;
+        }
+    }
+
+    /// Wakes the worker and makes `recv` return `None`; a pending frame is discarded.
+    pub fn close(&self) {
+        if let Ok(mut slot) = self.slot.lock() {
+            slot.closed = true;
+            slot.latest = None;
+        }
+        self.ready.notify_all();
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.slot.lock().map(|slot| slot.dropped).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn latest_frame_wins_and_replacements_are_counted() {
+        let channel = FrameChannel::new();
+        assert!(!channel.publish(1));
+        assert!(channel.publish(2));
+        assert!(channel.publish(3));
+
+        assert_eq!(channel.recv(), Some(3));
+        assert_eq!(channel.dropped(), 2);
+
+        // A consumed slot is empty again, so the next frame replaces nothing
+        assert!(!channel.publish(4));
+        assert_eq!(channel.dropped(), 2);
+    }
+
+    #[test]
+    fn close_ends_recv_and_discards_the_pending_frame() {
+        let channel = FrameChannel::new();
+        channel.publish(1);
+        channel.close();
+        assert_eq!(channel.recv(), None);
+        assert_eq!(channel.recv(), None);
+    }
+
+    #[test]
+    fn blocked_recv_wakes_on_publish_and_close() {
+        let channel = FrameChannel::new();
+        thread::scope(|scope| {
+            let worker = scope.spawn(|| (channel.recv(), channel.recv()));
+            // Give the worker time to block on the empty slot
+            thread::sleep(Duration::from_millis(50));
+            channel.publish(7);
+            // close() discards a pending frame, so wait until the worker has taken it
+            while channel.slot.lock().unwrap().latest.is_some() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            channel.close();
+            assert_eq!(worker.join().unwrap(), (Some(7), None));
+        });
+    }
+}
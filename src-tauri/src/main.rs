@@ -1,25 +1,32 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod breakdown;
+mod calibration;
 mod db;
+mod ergonomics;
+mod faults;
+mod frame_channel;
 mod commands;
 mod pose;
+mod posture_state;
+mod scoring;
+mod session;
+mod stance;
 mod state;
+mod stillness;
+mod tracking;
 
 use state::AppState;
-use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{TrayIconBuilder, MouseButton, TrayIconEvent},
-    Emitter, Manager, State, WindowEvent,
+    Manager, State, WindowEvent,
 };
 use std::collections::HashMap;
 use serde::Serialize;
 use rusqlite::params;
 use tauri_plugin_notification::NotificationExt;
 
-// GLOBAL FLAG: Controls the background thread
-static IS_TRACKING: AtomicBool = AtomicBool::new(false);
-
 // --- LEGACY STRUCTS ---
 #[derive(Serialize)]
 pub struct DashboardStatsStruct {
@@ -52,47 +59,116 @@ pub struct HourlyPoint {
 
 // --- COMMANDS ---
 
-// NOTE: We stripped out Nokhwa. These camera commands now just toggle the flag
-// or do nothing, assuming the Frontend handles the MediaStream directly.
-// We keep the signatures to avoid breaking the Frontend Bridge calling them.
+// Camera frames are captured and processed in tracking.rs (Black Box Rule):
+// the webview only ever receives landmarks via the `pose_update` event.
 
+// Privacy by Default: the device is opened lazily by `start_tracking`, never at boot
 #[tauri::command]
 fn init_camera(_state: State<AppState>) -> Result<String, String> {
-    // No-op for backend camera. Frontend uses navigator.mediaDevices
-    Ok("Backend Camera Init Skipped (Frontend Mode)".to_string())
+    Ok("Camera opens when tracking starts".to_string())
 }
 
+// Stopping joins the capture thread, so these run off the main thread
 #[tauri::command]
-fn kill_camera(_state: State<AppState>) -> bool {
-    IS_TRACKING.store(false, Ordering::Relaxed);
-    true
+async fn kill_camera(state: State<'_, AppState>) -> Result<bool, String> {
+    let app_state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || tracking::release_camera(&app_state).is_ok())
+        .await.map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-fn init_ai(app_handle: tauri::AppHandle, state: State<AppState>) -> Result<String, String> {
-    let resource_path = app_handle
+fn model_registry(app_handle: &tauri::AppHandle) -> Result<pose::ModelRegistry, String> {
+    let resources = app_handle
         .path()
         .resource_dir()
         .map_err(|e| format!("Path Error: {}", e))?
-        .join("resources/pose_model.onnx");
+        .join("resources");
+    let registry = pose::ModelRegistry::load(resources.join("models"))?;
 
-    if !resource_path.exists() {
-        return Err(format!("Model missing at {:?}", resource_path));
+    // Setups from before the manifest keep a single resources/pose_model.onnx; use it until it is listed
+    if registry.models().is_empty() && resources.join(pose::LEGACY_MODEL_FILE).exists() {
+        println!("⚠️ No models in resources/models/manifest.json, using unverified legacy {}", pose::LEGACY_MODEL_FILE);
+        return Ok(pose::ModelRegistry::legacy(resources));
     }
-    state.pose_engine.load_model(resource_path)
+    Ok(registry)
+}
+
+#[derive(Serialize)]
+pub struct ModelInfo {
+    #[serde(flatten)]
+    pub entry: pose::ModelEntry,
+    pub active: bool,
+}
+
+#[tauri::command]
+fn init_ai(app_handle: tauri::AppHandle, state: State<AppState>) -> Result<String, String> {
+    let registry = model_registry(&app_handle)?;
+    let settings = db::current_settings(state.inner());
+    // Last model the user switched to, falling back to the first one in the manifest
+    let entry = registry.find(settings.get("active_model").map(String::as_str))
+        .or_else(|_| registry.find(None))?;
+    state.pose_engine.load_model(&registry, entry, &pose::ModelConfig::from_settings(&settings))
+}
+
+#[tauri::command]
+fn list_models(app_handle: tauri::AppHandle, state: State<AppState>) -> Result<Vec<ModelInfo>, String> {
+    let registry = model_registry(&app_handle)?;
+    let active = state.pose_engine.active_model();
+    Ok(registry.models().iter().map(|entry| ModelInfo {
+        entry: entry.clone(),
+        active: active.as_ref() == Some(entry),
+    }).collect())
+}
+
+// Hot swap: the tracker keeps going and picks up the new session on its next frame
+#[tauri::command]
+async fn switch_model(app_handle: tauri::AppHandle, state: State<'_, AppState>, name: String) -> Result<String, String> {
+    let registry = model_registry(&app_handle)?;
+    let entry = registry.find(Some(&name))?.clone();
+    let config = pose::ModelConfig::from_settings(&db::current_settings(state.inner()));
+    let app_state = state.inner().clone();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let message = app_state.pose_engine.load_model(&registry, &entry, &config)?;
+        if let Some(conn) = app_state.db.lock().map_err(|_| "Failed to lock DB")?.as_ref() {
+            db::store_setting(conn, "active_model", &entry.name)?;
+        }
+        Ok(message)
+    }).await.map_err(|e| e.to_string())?
+}
+
+// Loads a throwaway engine with the *current* settings, so ORT options can be compared
+// without restarting the app or disturbing the live tracker.
+#[tauri::command]
+async fn benchmark_model(app_handle: tauri::AppHandle, state: State<'_, AppState>, iterations: Option<usize>, model: Option<String>) -> Result<pose::benchmark::BenchmarkReport, String> {
+    let registry = model_registry(&app_handle)?;
+    let settings = db::current_settings(state.inner());
+    let entry = registry.find(model.as_deref().or(settings.get("active_model").map(String::as_str)))?.clone();
+    let config = pose::ModelConfig::from_settings(&settings);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let engine = pose::PoseEngine::new();
+        engine.load_model(&registry, &entry, &config)?;
+        let report = pose::benchmark::run(&engine, config.runtime, iterations.unwrap_or(50))?;
+        println!("⏱️ Benchmark {}: p50 {:.1}ms, p95 {:.1}ms over {} runs ({:?})",
+            entry.version_tag(), report.p50_ms, report.p95_ms, report.iterations, report.config);
+        Ok(report)
+    }).await.map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
-fn start_tracking(_app_handle: tauri::AppHandle, _state: State<AppState>) -> Result<String, String> {
-    // Just set the flag, no actual backend tracking loop
-    IS_TRACKING.store(true, Ordering::Relaxed);
-    Ok("Tracking Flag Set".to_string())
+async fn start_tracking(app_handle: tauri::AppHandle, state: State<'_, AppState>) -> Result<String, String> {
+    let app_state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || tracking::start(app_handle, app_state))
+        .await.map_err(|e| e.to_string())?
 }
 
+// Releases the camera too: it was opened by `start_tracking`, so the LED goes off with tracking
 #[tauri::command]
-fn stop_tracking() -> String {
-    IS_TRACKING.store(false, Ordering::Relaxed);
-    "Tracking Stopped".to_string()
+async fn stop_tracking(state: State<'_, AppState>) -> Result<String, String> {
+    let app_state = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || tracking::release_camera(&app_state))
+        .await.map_err(|e| e.to_string())??;
+    Ok("Tracking Stopped".to_string())
 }
 
 // --- DB / HELPER COMMANDS ---
@@ -101,7 +177,7 @@ fn stop_tracking() -> String {
 fn save_setting(state: State<AppState>, key: String, value: String) -> Result<String, String> {
     let db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
     if let Some(conn) = db_lock.as_ref() {
-        conn.execute("INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)", params![key, value]).map_err(|e| e.to_string())?;
+        db::store_setting(conn, &key, &value)?;
         Ok("Saved".to_string())
     } else { Err("No DB".to_string()) }
 }
@@ -110,11 +186,7 @@ fn save_setting(state: State<AppState>, key: String, value: String) -> Result<St
 fn get_settings(state: State<AppState>) -> Result<HashMap<String, String>, String> {
     let db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
     if let Some(conn) = db_lock.as_ref() {
-        let mut stmt = conn.prepare("SELECT key, value FROM settings").map_err(|e| e.to_string())?;
-        let rows = stmt.query_map([], |r| Ok((r.get(0)?, r.get(1)?))).map_err(|e| e.to_string())?;
-        let mut map = HashMap::new();
-        for r in rows { let (k,v) = r.map_err(|e| e.to_string())?; map.insert(k, v); }
-        Ok(map)
+        db::load_settings(conn)
     } else { Err("No DB".to_string()) }
 }
 
@@ -203,8 +275,11 @@ fn main() {
             }
         })
         .invoke_handler(tauri::generate_handler![
-            init_camera, kill_camera, init_ai, start_tracking, stop_tracking,
+            init_camera, kill_camera, init_ai, list_models, switch_model, benchmark_model, start_tracking, stop_tracking,
             db::init_db,
+            calibration::calibrate,
+            calibration::get_calibration,
+            calibration::clear_calibration,
             commands::save_session,
             commands::get_report_data,
             commands::get_recent_sessions,
@@ -1,10 +1,45 @@
-use ort::session::{Session, builder::GraphOptimizationLevel};
-use ort::value::Tensor;
+use image::RgbImage;
+use std::collections::HashMap;
 use std::sync::Mutex;
-use std::path::PathBuf;
-use image::{imageops::FilterType, GenericImageView};
 
-#[derive(Debug, Clone, serde::Serialize)]
+mod angles;
+pub mod benchmark;
+mod decoder;
+mod estimator;
+mod head_pose;
+mod input;
+pub mod keypoints;
+mod letterbox;
+#[cfg(test)]
+pub mod mock;
+#[cfg(feature = "backend-ort")]
+mod ort_backend;
+mod pixel;
+pub mod quality;
+mod registry;
+mod roi;
+mod runtime;
+mod selection;
+mod smoothing;
+#[cfg(feature = "backend-tract")]
+mod tract_backend;
+mod world;
+
+pub use angles::PostureAngles;
+pub use decoder::DecodedPose;
+pub use estimator::{Backend, PoseEstimator};
+pub use head_pose::HeadPose;
+pub use input::InputConfig;
+pub use pixel::{ColorMatrix, Frame, PixelFormat};
+pub use quality::{FrameQuality, QualityConfig, QualityIssue};
+pub use registry::{ModelEntry, ModelRegistry, LEGACY_MODEL_FILE};
+pub use roi::{Roi, RoiTracker};
+pub use runtime::{OptimizationLevel, RuntimeConfig};
+pub use selection::{Anchor, PrimarySelector, Selection};
+pub use smoothing::{LandmarkSmoother, SmoothingConfig};
+pub use world::DepthMetrics;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Landmark {
     pub x: f32, 
     pub y: f32, 
@@ -12,136 +47,320 @@ pub struct Landmark {
     pub visibility: f32, 
 }
 
+/// Everything the settings table can say about how a model is loaded.
+/// `pose_backend` is "ort", "tract" or "auto" (default: first compiled-in backend that loads).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelConfig {
+    pub input: InputConfig,
+    pub runtime: RuntimeConfig,
+    pub backend: Option<Backend>,
+}
+
+impl ModelConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        Self {
+            input: InputConfig::from_settings(settings),
+            runtime: RuntimeConfig::from_settings(settings),
+            backend: settings.get("pose_backend").and_then(|v| Backend::parse(v)),
+        }
+    }
+}
+
+// The active estimator, the manifest entry it was loaded from and the settings it was
+// loaded with (`None` when a test installed it directly)
+struct LoadedModel {
+    estimator: Box<dyn PoseEstimator>,
+    entry: ModelEntry,
+    config: Option<ModelConfig>,
+}
+
 pub struct PoseEngine {
-    session: Mutex<Option<Session>>,
+    model: Mutex<Option<LoadedModel>>,
 }
 
 impl PoseEngine {
     pub fn new() -> Self {
-        Self { session: Mutex::new(None) }
+        Self { model: Mutex::new(None) }
     }
 
-    pub fn load_model(&self, resource_path: PathBuf) -> Result<String, String> {
-        let mut session_lock = self.session.lock().map_err(|_| "Failed to lock engine")?;
-        if session_lock.is_some() { return Ok("Model already loaded".to_string()); }
+    /// Verifies and loads `entry` from the registry. Replaces whatever model is active, so
+    /// this doubles as a hot swap: tracking keeps running on the old session until the new
+    /// one is ready.
+    pub fn load_model(&self, registry: &ModelRegistry, entry: &ModelEntry, config: &ModelConfig) -> Result<String, String> {
+        // Same model but changed input/runtime settings still needs a fresh session
+        let unchanged = self.model.lock().map_err(|_| "Failed to lock engine")?.as_ref()
+            .is_some_and(|loaded| &loaded.entry == entry && loaded.config.as_ref() == Some(config));
+        if unchanged {
+            return Ok("Model already loaded".to_string());
+        }
 
-        println!("🧠 Loading AI Model from: {:?}", resource_path);
+        let resource_path = registry.verified_path(entry)?;
+        println!("🧠 Loading AI Model {} from: {:?}", entry.version_tag(), resource_path);
 
-        let model = Session::builder()
-            .map_err(|e| format!("Builder error: {}", e))?
-            .with_optimization_level(GraphOptimizationLevel::Level3)
-            .map_err(|e| format!("Optimization error: {}", e))?
-            .with_intra_threads(1)
-            .map_err(|e| format!("Threads error: {}", e))? 
-            .commit_from_file(resource_path)
-            .map_err(|e| format!("Load error: {}", e))?;
+        let estimator = estimator::load(&resource_path, entry, config)?;
+        println!("✅ AI Model Loaded Successfully! ({}, runtime: {:?})", estimator.describe(), config.runtime);
 
-        *session_lock = Some(model);
-        println!("✅ AI Model Loaded Successfully!");
+        self.swap(LoadedModel { estimator, entry: entry.clone(), config: Some(config.clone()) })?;
         Ok("AI Brain Online".to_string())
     }
 
-    pub fn infer(&self, image_buffer: Vec<u8>, width: u32, height: u32) -> Result<Vec<Landmark>, String> {
-        let mut session_lock = self.session.lock().map_err(|_| "Failed to lock engine")?;
-        let session = session_lock.as_mut().ok_or("Model not loaded")?;
-
-        // 1. Preprocessing (Resize & Normalize)
-        let img = if image_buffer.len() == (width * height * 2) as usize {
-            // Case A: YUYV Format (2 bytes per pixel) - Common on macOS
-            // YUYV structure: [Y0, U, Y1, V] -> 4 bytes for 2 pixels
-            let mut rgb_buffer = Vec::with_capacity((width * height * 3) as usize);
-            
-            for chunk in image_buffer.chunks_exact(4) {
-                let y0 = chunk[0] as f32;
-                let u  = chunk[1] as f32 - 128.0;
-                let y1 = chunk[2] as f32;
-                let v  = chunk[3] as f32 - 128.0;
-
-                // Pixel 0 (Y0, U, V)
-                let r0 = (y0 + 1.402 * v).max(0.0).min(255.0) as u8;
-                let g0 = (y0 - 0.344136 * u - 0.714136 * v).max(0.0).min(255.0) as u8;
-                let b0 = (y0 + 1.772 * u).max(0.0).min(255.0) as u8;
-
-                // Pixel 1 (Y1, U, V)
-                let r1 = (y1 + 1.402 * v).max(0.0).min(255.0) as u8;
-                let g1 = (y1 - 0.344136 * u - 0.714136 * v).max(0.0).min(255.0) as u8;
-                let b1 = (y1 + 1.772 * u).max(0.0).min(255.0) as u8;
-
-                rgb_buffer.extend_from_slice(&[r0, g0, b0, r1, g1, b1]);
+    /// Makes `estimator` the one `infer` uses. Tests use it directly to run the engine
+    /// on a scripted estimator.
+    pub fn install(&self, estimator: Box<dyn PoseEstimator>, entry: ModelEntry) -> Result<(), String> {
+        self.swap(LoadedModel { estimator, entry, config: None })
+    }
+
+    fn swap(&self, loaded: LoadedModel) -> Result<(), String> {
+        let mut model_lock = self.model.lock().map_err(|_| "Failed to lock engine")?;
+        if let Some(previous) = model_lock.as_ref() {
+            println!("🔁 Swapped model {} -> {}", previous.entry.version_tag(), loaded.entry.version_tag());
+        }
+        *model_lock = Some(loaded);
+        Ok(())
+    }
+
+    /// Backend and negotiated input of the active model, for logs and benchmarks.
+    pub fn describe(&self) -> Option<String> {
+        self.model.lock().ok()?.as_ref().map(|loaded| loaded.estimator.describe())
+    }
+
+    /// Manifest entry of the model currently serving `infer`.
+    pub fn active_model(&self) -> Option<ModelEntry> {
+        self.model.lock().ok()?.as_ref().map(|loaded| loaded.entry.clone())
+    }
+
+    /// Runs the model on `frame`, or only on `roi` of it when tracking. Returns every person
+    /// the model reports, landmarks normalised to the full frame either way.
+    pub fn infer(&self, frame: &Frame, roi: Option<Roi>) -> Result<Vec<DecodedPose>, String> {
+        self.infer_rgb(&frame.to_rgb()?, roi)
+    }
+
+    /// Same as `infer` for a frame that is already RGB, so callers that also need the
+    /// pixels (e.g. quality checks) convert only once.
+    pub fn infer_rgb(&self, img: &RgbImage, roi: Option<Roi>) -> Result<Vec<DecodedPose>, String> {
+        let mut model_lock = self.model.lock().map_err(|_| "Failed to lock engine")?;
+        let loaded = model_lock.as_mut().ok_or("Model not loaded")?;
+
+        let mut poses = match roi {
+            Some(roi) => loaded.estimator.estimate(&roi.crop(img))?,
+            None => loaded.estimator.estimate(img)?,
+        };
+
+        // Back into original-frame normalised space (world landmarks are hip-centred, not cropped)
+        if let Some(roi) = roi {
+            for lm in poses.iter_mut().flat_map(|pose| pose.landmarks.iter_mut()) {
+                roi.unproject(lm);
             }
-            
-            image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(width, height, rgb_buffer)
-                 .ok_or("Failed to create RGB buffer from YUYV")?
-
-        } else if image_buffer.len() == (width * height * 3) as usize {
-            // Case B: Standard RGB
-            image::ImageBuffer::<image::Rgb<u8>, _>::from_raw(width, height, image_buffer)
-                .ok_or("Failed to create RGB buffer")?
-        } else {
-             return Err(format!("Buffer Mismatch! Expected {} (RGB) or {} (YUYV) bytes, got {} bytes", 
-                width * height * 3, width * height * 2, image_buffer.len()));
+        }
+        Ok(poses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use serde::{Deserialize, Serialize};
+    use super::*;
+    use super::input::InputDType;
+    use super::letterbox::ResizeMode;
+    use super::mock::{seated_user, test_entry, FixtureEstimator, MockEstimator};
+
+    // Golden landmarks are stored to 6 decimals
+    const GOLDEN_TOLERANCE: f32 = 1e-4;
+
+    /// One case in tests/fixtures/pose: a frame, a scripted model output and the landmarks
+    /// the engine must produce. Run with UPDATE_GOLDENS=1 to rewrite `expected` in place.
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Fixture {
+        description: String,
+        frame: FrameSpec,
+        model: ModelSpec,
+        resize_mode: String,
+        output: Vec<f32>,
+        expected: Expected,
+    }
+
+    /// Synthetic buffers use `fill` in the frame's own format (RGB or Y/U/V);
+    /// still images name a file next to the fixture.
+    #[derive(Debug, Serialize, Deserialize)]
+    struct FrameSpec {
+        format: String,
+        width: u32,
+        height: u32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fill: Option<[u8; 3]>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        image: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct ModelSpec {
+        input_shape: Vec<i64>,
+        input_dtype: String,
+        output_shape: Vec<i64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        output_layout: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Expected {
+        input_mean: f64,
+        input_mean_tolerance: f64,
+        poses: Vec<ExpectedPose>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct ExpectedPose {
+        score: f32,
+        landmarks: Vec<[f32; 4]>,
+    }
+
+    fn fixture_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pose")
+    }
+
+    fn build_frame(spec: &FrameSpec, dir: &Path) -> Frame {
+        let (w, h) = (spec.width as usize, spec.height as usize);
+        let fill = spec.fill.unwrap_or([0, 0, 0]);
+        match spec.format.as_str() {
+            "rgb" => Frame::new(fill.repeat(w * h), spec.width, spec.height, PixelFormat::Rgb),
+            "yuyv" => {
+                let [y, u, v] = fill;
+                Frame::new([y, u, y, v].repeat(w * h / 2), spec.width, spec.height, PixelFormat::Yuyv)
+            }
+            "nv12" => {
+                let [y, u, v] = fill;
+                let mut data = vec![y; w * h];
+                data.extend([u, v].repeat(w * h / 4));
+                Frame::new(data, spec.width, spec.height, PixelFormat::Nv12)
+            }
+            "png" => {
+                let path = dir.join(spec.image.as_deref().expect("png fixture needs an image"));
+                let img = image::open(&path).unwrap_or_else(|e| panic!("{:?}: {}", path, e)).to_rgb8();
+                assert_eq!(img.dimensions(), (spec.width, spec.height), "{:?} size", path);
+                Frame::new(img.into_raw(), spec.width, spec.height, PixelFormat::Rgb)
+            }
+            other => panic!("unknown fixture frame format {}", other),
+        }
+    }
+
+    fn run_fixture(fixture: &Fixture, dir: &Path) -> Result<(f64, Vec<DecodedPose>), String> {
+        let dtype = match fixture.model.input_dtype.as_str() {
+            "f32" => InputDType::F32,
+            "u8" => InputDType::U8,
+            "i32" => InputDType::I32,
+            other => return Err(format!("unknown input dtype {}", other)),
         };
+        let resize_mode = ResizeMode::parse(&fixture.resize_mode).ok_or("unknown resize mode")?;
+        let estimator = FixtureEstimator::new(&fixture.model.input_shape, dtype, &fixture.model.output_shape,
+            fixture.model.output_layout.clone(), resize_mode, fixture.output.clone())?;
+        let input_mean = estimator.input_mean.clone();
+
+        let engine = PoseEngine::new();
+        engine.install(Box::new(estimator), test_entry("fixture"))?;
+        let poses = engine.infer(&build_frame(&fixture.frame, dir), None)?;
+        let mean = input_mean.lock().unwrap().ok_or("estimator never ran")?;
+        Ok((mean, poses))
+    }
 
-        let img = image::DynamicImage::ImageRgb8(img);
-        let resized = img.resize_exact(256, 256, FilterType::Triangle);
-
-        // 2. Prepare Data Vector [1, 256, 256, 3] (NHWC)
-        // The error "Got: 3 Expected: 256" at index 1 confirms the model wants NHWC
-        let mut input_data = Vec::with_capacity(1 * 256 * 256 * 3);
-        
-        for (_x, _y, pixel) in resized.pixels() {
-            // Normalize to 0.0 - 1.0 range
-            input_data.push(pixel[0] as f32 / 255.0);
-            input_data.push(pixel[1] as f32 / 255.0);
-            input_data.push(pixel[2] as f32 / 255.0);
+    fn compare(fixture: &Fixture, mean: f64, poses: &[DecodedPose]) -> Vec<String> {
+        let expected = &fixture.expected;
+        let mut problems = Vec::new();
+        if (mean - expected.input_mean).abs() > expected.input_mean_tolerance {
+            problems.push(format!("input mean {} (expected {})", mean, expected.input_mean));
+        }
+        if poses.len() != expected.poses.len() {
+            problems.push(format!("{} poses (expected {})", poses.len(), expected.poses.len()));
+            return problems;
         }
+        for (p, (pose, golden)) in poses.iter().zip(&expected.poses).enumerate() {
+            if (pose.score - golden.score).abs() > GOLDEN_TOLERANCE {
+                problems.push(format!("pose {} score {} (expected {})", p, pose.score, golden.score));
+            }
+            for (i, (lm, g)) in pose.landmarks.iter().zip(&golden.landmarks).enumerate() {
+                let actual = [lm.x, lm.y, lm.z, lm.visibility];
+                if actual.iter().zip(g).any(|(a, e)| (a - e).abs() > GOLDEN_TOLERANCE) {
+                    problems.push(format!("pose {} landmark {}: {:?} (expected {:?})", p, i, actual, g));
+                }
+            }
+        }
+        problems
+    }
+
+    #[test]
+    fn fixtures_match_goldens() {
+        let dir = fixture_dir();
+        let update = std::env::var("UPDATE_GOLDENS").is_ok();
+        let mut paths: Vec<PathBuf> = fs::read_dir(&dir).unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect();
+        paths.sort();
+        assert!(!paths.is_empty(), "no fixtures in {:?}", dir);
 
-        // 3. Create Tensor from (Shape, Data) Tuple
-        // Shape is now [1, 256, 256, 3]
-        let input_tensor = Tensor::from_array(([1, 256, 256, 3], input_data))
-             .map_err(|e| {
-                 println!("❌ Tensor Creation Failed: {}", e);
-                 format!("Tensor creation failed: {}", e)
-             })?;
-
-        // 4. Run Inference
-        // DYNAMIC INPUT NAME FIX:
-        // Clone names to avoid holding a borrow on 'session' while running it
-        let input_name = session.inputs()[0].name().to_string();
-        let output_name = session.outputs()[0].name().to_string();
-        
-        let inputs = ort::inputs![input_name.as_str() => input_tensor];
-        let outputs = session.run(inputs)
-            .map_err(|e| {
-                println!("❌ Session Run Failed: {}", e);
-                format!("Inference failed: {}", e)
-            })?;
-
-        // 5. Extract Output
-        // try_extract_tensor returns (Shape, DataSlice) in v2.0
-        
-        let output_tuple = outputs[output_name.as_str()].try_extract_tensor::<f32>()
-            .map_err(|e| {
-                println!("❌ Tensor Extraction Failed: {}", e);
-                format!("Extraction failed: {}", e)
-            })?;
-        
-        let data = output_tuple.1; // Access the data slice directly (Index 1 of tuple)
-
-        // 6. Parse 33 Landmarks
-        let mut landmarks = Vec::new();
-        for i in 0..33 {
-            let offset = i * 5; 
-            if offset + 4 < data.len() {
-                landmarks.push(Landmark {
-                    x: data[offset],     
-                    y: data[offset + 1],
-                    z: data[offset + 2],
-                    visibility: data[offset + 3], 
-                });
+        let mut failures = Vec::new();
+        for path in paths {
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            let mut fixture: Fixture = serde_json::from_str(&fs::read_to_string(&path).unwrap())
+                .unwrap_or_else(|e| panic!("{}: {}", name, e));
+
+            let (mean, poses) = match run_fixture(&fixture, &dir) {
+                Ok(result) => result,
+                Err(e) => { failures.push(format!("{}: {}", name, e)); continue; }
+            };
+
+            if update {
+                fixture.expected.input_mean = mean;
+                fixture.expected.poses = poses.iter().map(|pose| ExpectedPose {
+                    score: pose.score,
+                    landmarks: pose.landmarks.iter().map(|lm| [lm.x, lm.y, lm.z, lm.visibility]).collect(),
+                }).collect();
+                fs::write(&path, serde_json::to_string_pretty(&fixture).unwrap() + "\n").unwrap();
+                continue;
             }
+            failures.extend(compare(&fixture, mean, &poses).into_iter().map(|p| format!("{}: {}", name, p)));
         }
+        assert!(failures.is_empty(), "fixture mismatches:\n{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn roi_results_are_mapped_back_to_the_frame() {
+        let mock = MockEstimator::constant(seated_user(0.5));
+        let seen = mock.seen.clone();
 
-        Ok(landmarks)
+        let engine = PoseEngine::new();
+        engine.install(Box::new(mock), test_entry("mock")).unwrap();
+        let frame = Frame::new(vec![128; 200 * 100 * 3], 200, 100, PixelFormat::Rgb);
+        let roi = Roi { x: 0.5, y: 0.0, width: 0.5, height: 1.0 };
+        let poses = engine.infer(&frame, Some(roi)).unwrap();
+
+        assert_eq!(seen.lock().unwrap().as_slice(), &[(100, 100)], "estimator should only see the crop");
+        let nose = &poses[0].landmarks[keypoints::NOSE];
+        assert!((nose.x - 0.75).abs() < 1e-6, "crop centre should land at 0.75, got {}", nose.x);
+        assert!((nose.y - 0.30).abs() < 1e-6);
+    }
+
+    #[test]
+    fn changed_settings_reload_the_same_model() {
+        // A legacy registry pointing nowhere: any real reload attempt fails with "Model missing"
+        let registry = ModelRegistry::legacy(std::env::temp_dir().join("posture-no-such-dir"));
+        let entry = registry.find(None).unwrap().clone();
+        let config = ModelConfig::default();
+
+        let engine = PoseEngine::new();
+        let mock = Box::new(MockEstimator::constant(seated_user(0.5)));
+        engine.swap(LoadedModel { estimator: mock, entry: entry.clone(), config: Some(config.clone()) }).unwrap();
+        assert_eq!(engine.load_model(&registry, &entry, &config).unwrap(), "Model already loaded");
+
+        let resized = ModelConfig { input: InputConfig { input_size: Some(192), ..config.input.clone() }, ..config };
+        let err = engine.load_model(&registry, &entry, &resized).unwrap_err();
+        assert!(err.contains("Model missing"), "should have tried to reload, got {}", err);
+    }
+
+    #[test]
+    fn infer_without_a_model_is_an_error() {
+        let frame = Frame::new(vec![0; 12], 2, 2, PixelFormat::Rgb);
+        assert_eq!(PoseEngine::new().infer(&frame, None).unwrap_err(), "Model not loaded");
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use super::keypoints::*;
+use super::{HeadPose, Landmark};
+
+// Acromion midpoint to ear over biacromial width, for an adult sitting upright.
+// Lets a front camera estimate the craniovertebral angle without depth.
+const NECK_TO_SHOULDER_WIDTH: f32 = 0.55;
+// Biacromial over bitragion breadth (~38 cm / ~14.5 cm): shoulders look this much wider
+// than the head when they are not rolled forward
+const SHOULDER_TO_HEAD_WIDTH: f32 = 2.6;
+// Past this much head yaw the ears stop being a usable width reference
+const MAX_YAW_FOR_PROTRACTION: f32 = 20.0;
+
+/// Ergonomic angles in degrees. Unlike pixel offsets they do not change when the laptop
+/// moves closer or the camera resolution changes. Each is None when the points it needs
+/// are not visible.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PostureAngles {
+    /// Shoulder-to-ear line above horizontal, seen from the side. ~50+ is upright,
+    /// lower means the head sits forward. Metric with world landmarks, otherwise estimated
+    /// from how high the ears sit above the shoulders.
+    pub craniovertebral_deg: Option<f32>,
+    /// Head pitch: how far the user is looking down.
+    pub neck_flexion_deg: Option<f32>,
+    /// Shoulder line against horizontal, either side.
+    pub shoulder_tilt_deg: Option<f32>,
+    /// Hip-to-shoulder line against vertical. Forward and sideways with world landmarks,
+    /// sideways only without. Needs the hips, so often None at a desk.
+    pub trunk_inclination_deg: Option<f32>,
+    /// How far the shoulders are rolled forward, from how narrow they look next to the head.
+    pub shoulder_protraction_deg: Option<f32>,
+}
+
+impl PostureAngles {
+    /// `landmarks` are normalised image landmarks, `aspect` is frame width / height.
+    pub fn measure(landmarks: &[Landmark], world: Option<&[Landmark]>, head_pose: Option<&HeadPose>, aspect: f32) -> Self {
+        // Square pixels, so angles are not skewed by the frame's aspect ratio
+        let point = |i: usize| visible(landmarks, i).map(|lm| [lm.x * aspect, lm.y, 0.0]);
+        let world_point = |i: usize| visible(world?, i).map(|lm| [lm.x, lm.y, lm.z]);
+
+        let shoulders = point(LEFT_SHOULDER).zip(point(RIGHT_SHOULDER));
+        let ears = point(LEFT_EAR).zip(point(RIGHT_EAR));
+        let world_shoulders = world_point(LEFT_SHOULDER).zip(world_point(RIGHT_SHOULDER));
+
+        let craniovertebral_deg = match (world_shoulders, world_point(LEFT_EAR).zip(world_point(RIGHT_EAR))) {
+            (Some((ls, rs)), Some((le, re))) => {
+                let (s, e) = (mid(ls, rs), mid(le, re));
+                Some((s[1] - e[1]).atan2((s[2] - e[2]).abs()).to_degrees())
+            }
+            _ => shoulders.zip(ears).map(|((ls, rs), (le, re))| {
+                let rise = (mid(ls, rs)[1] - mid(le, re)[1]) / distance(ls, rs);
+                (rise / NECK_TO_SHOULDER_WIDTH).clamp(-1.0, 1.0).asin().to_degrees()
+            }),
+        };
+
+        let shoulder_tilt_deg = shoulders.map(|(ls, rs)| {
+            (ls[1] - rs[1]).abs().atan2((ls[0] - rs[0]).abs()).to_degrees()
+        });
+
+        let world_hips = world_point(LEFT_HIP).zip(world_point(RIGHT_HIP));
+        let hips = point(LEFT_HIP).zip(point(RIGHT_HIP));
+        let trunk_inclination_deg = match (world_shoulders, world_hips) {
+            (Some((ls, rs)), Some((lh, rh))) => Some(tilt_from_vertical(mid(lh, rh), mid(ls, rs))),
+            _ => shoulders.zip(hips).map(|((ls, rs), (lh, rh))| tilt_from_vertical(mid(lh, rh), mid(ls, rs))),
+        };
+
+        let facing_camera = !matches!(head_pose, Some(h) if h.yaw_deg.abs() > MAX_YAW_FOR_PROTRACTION);
+        let shoulder_protraction_deg = shoulders.zip(ears).filter(|_| facing_camera).map(|((ls, rs), (le, re))| {
+            let ratio = distance(ls, rs) / distance(le, re) / SHOULDER_TO_HEAD_WIDTH;
+            ratio.clamp(0.0, 1.0).acos().to_degrees()
+        });
+
+        Self {
+            craniovertebral_deg,
+            neck_flexion_deg: head_pose.map(|h| h.pitch_deg),
+            shoulder_tilt_deg,
+            trunk_inclination_deg,
+            shoulder_protraction_deg,
+        }
+    }
+}
+
+fn mid(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0, (a[2] + b[2]) / 2.0]
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+// y points down, so "up" is lower minus upper
+fn tilt_from_vertical(lower: [f32; 3], upper: [f32; 3]) -> f32 {
+    let horizontal = ((upper[0] - lower[0]).powi(2) + (upper[2] - lower[2]).powi(2)).sqrt();
+    horizontal.atan2(lower[1] - upper[1]).to_degrees()
+}
@@ -0,0 +1,61 @@
+use std::time::Instant;
+use serde::Serialize;
+use super::{Frame, PixelFormat, PoseEngine, RuntimeConfig};
+
+// Runs thrown away before timing: first calls pay for allocation and kernel selection
+const WARMUP_RUNS: usize = 5;
+// Typical webcam frame, so preprocessing cost is part of the number
+const FRAME_SIZE: (u32, u32) = (640, 480);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkReport {
+    /// Backend and input, e.g. "tract 256x256 Nhwc F32 blazepose"
+    pub estimator: String,
+    pub iterations: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub mean_ms: f64,
+    pub config: RuntimeConfig,
+}
+
+/// Times `iterations` full-frame inferences (preprocessing included) on a synthetic frame.
+pub fn run(engine: &PoseEngine, config: RuntimeConfig, iterations: usize) -> Result<BenchmarkReport, String> {
+    if iterations == 0 {
+        return Err("Benchmark needs at least one iteration".to_string());
+    }
+
+    // Mid-grey gradient rather than flat black, so nothing short-circuits on empty input
+    let (w, h) = FRAME_SIZE;
+    let data = (0..w * h).flat_map(|i| {
+        let v = (96 + (i % w) * 64 / w) as u8;
+        [v, v, v]
+    }).collect();
+    let frame = Frame::new(data, w, h, PixelFormat::Rgb);
+
+    for _ in 0..WARMUP_RUNS {
+        engine.infer(&frame, None)?;
+    }
+
+    let mut timings = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        engine.infer(&frame, None)?;
+        timings.push(start.elapsed().as_secs_f64() * 1000.0);
+    }
+    timings.sort_by(f64::total_cmp);
+
+    Ok(BenchmarkReport {
+        estimator: engine.describe().unwrap_or_default(),
+        iterations,
+        p50_ms: percentile(&timings, 0.50),
+        p95_ms: percentile(&timings, 0.95),
+        mean_ms: timings.iter().sum::<f64>() / timings.len() as f64,
+        config,
+    })
+}
+
+// Nearest-rank on an already sorted slice
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
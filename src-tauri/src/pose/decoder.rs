@@ -0,0 +1,331 @@
+use std::fmt;
+use super::Landmark;
+use super::keypoints::{COCO_TO_BLAZEPOSE, LANDMARK_COUNT};
+
+// Model metadata key that lets a custom model describe its own output layout.
+// Format: "<keypoints>:<channels>[:px]", e.g. "17:yxs" or "33:xyzvp:px"
+//   x/y = coordinates, z = depth, s = score (0..1), v = visibility logit, anything else is skipped
+//   ":px" means coordinates are in input pixels instead of 0..1
+// Also accepts a built-in decoder name: "blazepose", "movenet-singlepose", "movenet-multipose"
+pub const LAYOUT_METADATA_KEY: &str = "pose_layout";
+
+const BLAZEPOSE_STRIDE: usize = 5;
+const WORLD_STRIDE: usize = 3;
+const MOVENET_KEYPOINTS: usize = 17;
+const MOVENET_STRIDE: usize = 3;
+const MULTIPOSE_MAX_PEOPLE: usize = 6;
+const MULTIPOSE_STRIDE: usize = 56; // 17 * (y, x, score) + [ymin, xmin, ymax, xmax, score]
+// MultiPose always fills its 6 slots; empty ones carry small but non-zero scores
+pub const MIN_PERSON_SCORE: f32 = 0.3;
+
+#[derive(Debug, Clone)]
+pub struct DecodedPose {
+    pub landmarks: Vec<Landmark>,
+    pub score: f32,
+    /// Metric 3D landmarks (metres, origin between the hips) when the model has a world output.
+    pub world: Option<Vec<Landmark>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    UnknownLayout(Vec<i64>),
+    InvalidLayoutHint(String),
+    OutputTooShort { expected: usize, got: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnknownLayout(shape) => write!(f, "Unknown pose output layout {:?} (set '{}' in the model metadata)", shape, LAYOUT_METADATA_KEY),
+            DecodeError::InvalidLayoutHint(hint) => write!(f, "Invalid '{}' value: {:?}", LAYOUT_METADATA_KEY, hint),
+            DecodeError::OutputTooShort { expected, got } => write!(f, "Pose output too short: expected {} values, got {}", expected, got),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Channel { X, Y, Z, Score, VisibilityLogit, Skip }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomLayout {
+    keypoints: usize,
+    channels: Vec<Channel>,
+    pixel_coords: bool,
+}
+
+impl CustomLayout {
+    pub fn parse(hint: &str) -> Result<Self, DecodeError> {
+        let invalid = || DecodeError::InvalidLayoutHint(hint.to_string());
+        let mut parts = hint.trim().split(':');
+
+        let keypoints: usize = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        if keypoints != MOVENET_KEYPOINTS && keypoints != LANDMARK_COUNT { return Err(invalid()); }
+
+        let channels: Vec<Channel> = parts.next().ok_or_else(invalid)?.chars().map(|c| match c {
+            'x' => Channel::X,
+            'y' => Channel::Y,
+            'z' => Channel::Z,
+            's' => Channel::Score,
+            'v' => Channel::VisibilityLogit,
+            _ => Channel::Skip,
+        }).collect();
+        if !channels.contains(&Channel::X) || !channels.contains(&Channel::Y) { return Err(invalid()); }
+
+        let pixel_coords = match parts.next() {
+            None => false,
+            Some("px") => true,
+            Some(_) => return Err(invalid()),
+        };
+
+        Ok(Self { keypoints, channels, pixel_coords })
+    }
+}
+
+/// Output parser chosen once per model from its output shape (or metadata hint).
+#[derive(Debug, Clone, PartialEq)]
+pub enum PoseDecoder {
+    /// BlazePose landmark model: [1, 195] = 39 x (x, y, z, visibility, presence) in input pixels.
+    BlazePose,
+    /// MoveNet Lightning/Thunder: [1, 1, 17, 3] = (y, x, score) normalised.
+    MoveNetSinglePose,
+    /// MoveNet MultiPose: [1, 6, 56] = 6 people x (17 keypoints + box + score).
+    MoveNetMultiPose,
+    Custom(CustomLayout),
+}
+
+impl PoseDecoder {
+    pub fn from_output(shape: &[i64], layout_hint: Option<&str>) -> Result<Self, DecodeError> {
+        // A hint is either one of our decoder names or a custom layout string
+        match layout_hint {
+            Some("blazepose") => return Ok(PoseDecoder::BlazePose),
+            Some("movenet-singlepose") => return Ok(PoseDecoder::MoveNetSinglePose),
+            Some("movenet-multipose") => return Ok(PoseDecoder::MoveNetMultiPose),
+            Some(hint) => return CustomLayout::parse(hint).map(PoseDecoder::Custom),
+            None => {}
+        }
+
+        // Drop batch dims (fixed 1 or dynamic -1) so [1, 1, 17, 3] and [-1, 195] both match
+        let dims: Vec<i64> = shape.iter().copied().skip_while(|&d| d == 1 || d == -1).collect();
+        match dims.as_slice() {
+            [17, 3] => Ok(PoseDecoder::MoveNetSinglePose),
+            [6, 56] => Ok(PoseDecoder::MoveNetMultiPose),
+            [33, 5] | [39, 5] => Ok(PoseDecoder::BlazePose),
+            [n] if *n >= (LANDMARK_COUNT * BLAZEPOSE_STRIDE) as i64 && *n % BLAZEPOSE_STRIDE as i64 == 0 => Ok(PoseDecoder::BlazePose),
+            _ => Err(DecodeError::UnknownLayout(shape.to_vec())),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PoseDecoder::BlazePose => "blazepose",
+            PoseDecoder::MoveNetSinglePose => "movenet-singlepose",
+            PoseDecoder::MoveNetMultiPose => "movenet-multipose",
+            PoseDecoder::Custom(_) => "custom",
+        }
+    }
+
+    /// Decodes raw output into poses, landmarks normalised to 0..1 of the model input.
+    pub fn decode(&self, data: &[f32], input_size: (u32, u32)) -> Result<Vec<DecodedPose>, DecodeError> {
+        match self {
+            PoseDecoder::BlazePose => decode_blazepose(data, input_size),
+            PoseDecoder::MoveNetSinglePose => {
+                require_len(data, MOVENET_KEYPOINTS * MOVENET_STRIDE)?;
+                Ok(vec![decode_movenet_keypoints(&data[..MOVENET_KEYPOINTS * MOVENET_STRIDE])])
+            }
+            PoseDecoder::MoveNetMultiPose => decode_multipose(data),
+            PoseDecoder::Custom(layout) => decode_custom(layout, data, input_size),
+        }
+    }
+}
+
+fn require_len(data: &[f32], expected: usize) -> Result<(), DecodeError> {
+    if data.len() < expected {
+        return Err(DecodeError::OutputTooShort { expected, got: data.len() });
+    }
+    Ok(())
+}
+
+fn sigmoid(v: f32) -> f32 {
+    1.0 / (1.0 + (-v).exp())
+}
+
+fn empty_landmarks() -> Vec<Landmark> {
+    vec![Landmark { x: 0.0, y: 0.0, z: 0.0, visibility: 0.0 }; LANDMARK_COUNT]
+}
+
+fn mean_visibility(landmarks: &[Landmark]) -> f32 {
+    let visible: Vec<f32> = landmarks.iter().map(|l| l.visibility).filter(|v| *v > 0.0).collect();
+    if visible.is_empty() { 0.0 } else { visible.iter().sum::<f32>() / visible.len() as f32 }
+}
+
+fn decode_blazepose(data: &[f32], (width, height): (u32, u32)) -> Result<Vec<DecodedPose>, DecodeError> {
+    require_len(data, LANDMARK_COUNT * BLAZEPOSE_STRIDE)?;
+    let (w, h) = (width as f32, height as f32);
+
+    let landmarks: Vec<Landmark> = data.chunks_exact(BLAZEPOSE_STRIDE)
+        .take(LANDMARK_COUNT)
+        .map(|c| Landmark {
+            x: c[0] / w,
+            y: c[1] / h,
+            z: c[2] / w,
+            visibility: sigmoid(c[3]),
+        })
+        .collect();
+
+    let score = mean_visibility(&landmarks);
+    Ok(vec![DecodedPose { landmarks, score, world: None }])
+}
+
+/// True for BlazePose's world output: [1, 117] = 39 x (x, y, z) in metres.
+pub fn is_world_output(shape: &[i64]) -> bool {
+    let dims: Vec<i64> = shape.iter().copied().skip_while(|&d| d == 1 || d == -1).collect();
+    // Exact sizes only: the landmark output ([1, 195]) is also a multiple of 3
+    matches!(dims.as_slice(), [33, 3] | [39, 3] | [99] | [117])
+}
+
+/// Decodes the world output. It carries no visibility, so each point borrows it from the
+/// matching image-space landmark.
+pub fn decode_world(data: &[f32], image: &[Landmark]) -> Result<Vec<Landmark>, DecodeError> {
+    require_len(data, LANDMARK_COUNT * WORLD_STRIDE)?;
+    Ok(data.chunks_exact(WORLD_STRIDE)
+        .take(LANDMARK_COUNT)
+        .zip(image)
+        .map(|(c, lm)| Landmark { x: c[0], y: c[1], z: c[2], visibility: lm.visibility })
+        .collect())
+}
+
+fn decode_movenet_keypoints(data: &[f32]) -> DecodedPose {
+    let mut landmarks = empty_landmarks();
+    for (i, kp) in data.chunks_exact(MOVENET_STRIDE).take(MOVENET_KEYPOINTS).enumerate() {
+        landmarks[COCO_TO_BLAZEPOSE[i]] = Landmark { x: kp[1], y: kp[0], z: 0.0, visibility: kp[2] };
+    }
+    let score = mean_visibility(&landmarks);
+    DecodedPose { landmarks, score, world: None }
+}
+
+fn decode_multipose(data: &[f32]) -> Result<Vec<DecodedPose>, DecodeError> {
+    require_len(data, MULTIPOSE_MAX_PEOPLE * MULTIPOSE_STRIDE)?;
+
+    Ok(data.chunks_exact(MULTIPOSE_STRIDE)
+        .take(MULTIPOSE_MAX_PEOPLE)
+        .map(|person| {
+            let mut pose = decode_movenet_keypoints(&person[..MOVENET_KEYPOINTS * MOVENET_STRIDE]);
+            pose.score = person[MULTIPOSE_STRIDE - 1];
+            pose
+        })
+        .filter(|pose| pose.score >= MIN_PERSON_SCORE)
+        .collect())
+}
+
+fn decode_custom(layout: &CustomLayout, data: &[f32], (width, height): (u32, u32)) -> Result<Vec<DecodedPose>, DecodeError> {
+    let stride = layout.channels.len();
+    require_len(data, layout.keypoints * stride)?;
+    let (sx, sy) = if layout.pixel_coords { (width as f32, height as f32) } else { (1.0, 1.0) };
+
+    let mut landmarks = empty_landmarks();
+    for (i, values) in data.chunks_exact(stride).take(layout.keypoints).enumerate() {
+        let mut lm = Landmark { x: 0.0, y: 0.0, z: 0.0, visibility: 1.0 };
+        for (channel, &v) in layout.channels.iter().zip(values) {
+            match channel {
+                Channel::X => lm.x = v / sx,
+                Channel::Y => lm.y = v / sy,
+                Channel::Z => lm.z = v / sx,
+                Channel::Score => lm.visibility = v,
+                Channel::VisibilityLogit => lm.visibility = sigmoid(v),
+                Channel::Skip => {}
+            }
+        }
+        let slot = if layout.keypoints == MOVENET_KEYPOINTS { COCO_TO_BLAZEPOSE[i] } else { i };
+        landmarks[slot] = lm;
+    }
+
+    let score = mean_visibility(&landmarks);
+    Ok(vec![DecodedPose { landmarks, score, world: None }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::keypoints::{LEFT_SHOULDER, NOSE};
+
+    const INPUT: (u32, u32) = (256, 128);
+
+    #[test]
+    fn layouts_are_picked_from_the_output_shape() {
+        assert_eq!(PoseDecoder::from_output(&[1, 195], None), Ok(PoseDecoder::BlazePose));
+        assert_eq!(PoseDecoder::from_output(&[-1, 33, 5], None), Ok(PoseDecoder::BlazePose));
+        assert_eq!(PoseDecoder::from_output(&[1, 1, 17, 3], None), Ok(PoseDecoder::MoveNetSinglePose));
+        assert_eq!(PoseDecoder::from_output(&[1, 6, 56], None), Ok(PoseDecoder::MoveNetMultiPose));
+        // A hint wins over the shape
+        assert_eq!(PoseDecoder::from_output(&[1, 6, 56], Some("blazepose")), Ok(PoseDecoder::BlazePose));
+    }
+
+    #[test]
+    fn unknown_layouts_and_bad_hints_are_errors() {
+        assert_eq!(PoseDecoder::from_output(&[1, 10, 7], None), Err(DecodeError::UnknownLayout(vec![1, 10, 7])));
+        assert_eq!(PoseDecoder::from_output(&[1, 196], None), Err(DecodeError::UnknownLayout(vec![1, 196])));
+        for hint in ["12:xy", "17:zs", "17", "33:xyz:cm", "many:xy"] {
+            assert_eq!(PoseDecoder::from_output(&[1, 195], Some(hint)), Err(DecodeError::InvalidLayoutHint(hint.to_string())));
+        }
+    }
+
+    #[test]
+    fn short_outputs_are_errors() {
+        assert_eq!(PoseDecoder::BlazePose.decode(&[0.0; 10], INPUT).unwrap_err(),
+                   DecodeError::OutputTooShort { expected: 165, got: 10 });
+        assert_eq!(PoseDecoder::MoveNetSinglePose.decode(&[0.0; 50], INPUT).unwrap_err(),
+                   DecodeError::OutputTooShort { expected: 51, got: 50 });
+        assert_eq!(PoseDecoder::MoveNetMultiPose.decode(&[0.0; 56], INPUT).unwrap_err(),
+                   DecodeError::OutputTooShort { expected: 336, got: 56 });
+    }
+
+    #[test]
+    fn blazepose_is_normalised_from_input_pixels() {
+        let mut data = vec![0.0; 39 * 5];
+        data[..5].copy_from_slice(&[128.0, 32.0, 25.6, 0.0, 1.0]);
+        let poses = PoseDecoder::BlazePose.decode(&data, INPUT).unwrap();
+        assert_eq!(poses.len(), 1);
+        assert_eq!(poses[0].landmarks.len(), LANDMARK_COUNT);
+        let nose = &poses[0].landmarks[NOSE];
+        assert_eq!((nose.x, nose.y, nose.z, nose.visibility), (0.5, 0.25, 0.1, 0.5));
+    }
+
+    #[test]
+    fn movenet_reads_y_before_x_into_blazepose_slots() {
+        let mut data = vec![0.0; 17 * 3];
+        data[..3].copy_from_slice(&[0.2, 0.6, 0.9]);
+        // COCO keypoint 5 is the left shoulder
+        data[15..18].copy_from_slice(&[0.5, 0.7, 0.8]);
+        let poses = PoseDecoder::MoveNetSinglePose.decode(&data, INPUT).unwrap();
+        let landmarks = &poses[0].landmarks;
+        assert_eq!((landmarks[NOSE].x, landmarks[NOSE].y, landmarks[NOSE].visibility), (0.6, 0.2, 0.9));
+        assert_eq!((landmarks[LEFT_SHOULDER].x, landmarks[LEFT_SHOULDER].y), (0.7, 0.5));
+        // BlazePose-only points stay invisible
+        assert_eq!(landmarks[1].visibility, 0.0);
+    }
+
+    #[test]
+    fn multipose_keeps_only_detected_people() {
+        let mut data = vec![0.0; 6 * 56];
+        data[..3].copy_from_slice(&[0.3, 0.4, 0.7]);
+        data[55] = 0.8;
+        data[2 * 56 + 55] = 0.5;
+        // Padding slots are never exactly zero
+        for slot in [1, 3, 4, 5] { data[slot * 56 + 55] = 0.02; }
+        let poses = PoseDecoder::MoveNetMultiPose.decode(&data, INPUT).unwrap();
+        assert_eq!(poses.iter().map(|p| p.score).collect::<Vec<_>>(), [0.8, 0.5]);
+        assert_eq!((poses[0].landmarks[NOSE].x, poses[0].landmarks[NOSE].y), (0.4, 0.3));
+    }
+
+    #[test]
+    fn custom_layouts_follow_the_hint() {
+        let decoder = PoseDecoder::from_output(&[1, 33, 4], Some("33:xy_s:px")).unwrap();
+        let mut data = vec![0.0; 33 * 4];
+        data[..4].copy_from_slice(&[64.0, 64.0, 123.0, 0.75]);
+        let poses = decoder.decode(&data, INPUT).unwrap();
+        let nose = &poses[0].landmarks[NOSE];
+        assert_eq!((nose.x, nose.y, nose.visibility), (0.25, 0.5, 0.75));
+    }
+}
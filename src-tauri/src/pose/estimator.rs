@@ -0,0 +1,143 @@
+use std::path::Path;
+use image::RgbImage;
+use serde::Serialize;
+use super::decoder::{decode_world, is_world_output, DecodedPose, PoseDecoder};
+use super::input::{InputData, InputDType, InputSpec};
+use super::letterbox::Letterbox;
+use super::{ModelConfig, ModelEntry};
+
+/// A loaded pose model. Takes an RGB image, returns every person it sees with landmarks
+/// normalised to that image. Cropping, smoothing and selection happen above this.
+pub trait PoseEstimator: Send {
+    /// Short description for logs, e.g. "ort 256x256 Nhwc F32 blazepose".
+    fn describe(&self) -> String;
+    fn estimate(&mut self, img: &RgbImage) -> Result<Vec<DecodedPose>, String>;
+}
+
+/// Inference runtime behind the estimator.
+///   ort:   ONNX Runtime, needs the onnxruntime shared library at run time
+///   tract: pure Rust, nothing to ship next to the binary
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend { Ort, Tract }
+
+impl Backend {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "ort" => Some(Backend::Ort),
+            "tract" => Some(Backend::Tract),
+            _ => None,
+        }
+    }
+
+    /// Backends compiled into this build (cargo features `backend-ort` / `backend-tract`),
+    /// in the order `auto` tries them.
+    pub fn available() -> Vec<Backend> {
+        let mut backends = Vec::new();
+        if cfg!(feature = "backend-ort") { backends.push(Backend::Ort); }
+        if cfg!(feature = "backend-tract") { backends.push(Backend::Tract); }
+        backends
+    }
+}
+
+/// Loads `path` with the backend chosen in `config`, or the first one that works when the
+/// setting is "auto" (so a machine without the ORT library still gets a working engine).
+pub fn load(path: &Path, entry: &ModelEntry, config: &ModelConfig) -> Result<Box<dyn PoseEstimator>, String> {
+    let candidates = match config.backend {
+        Some(backend) => vec![backend],
+        None => Backend::available(),
+    };
+    if candidates.is_empty() {
+        return Err("No inference backend compiled in (enable backend-ort or backend-tract)".to_string());
+    }
+
+    let mut errors = Vec::new();
+    for backend in candidates {
+        match load_with(backend, path, entry, config) {
+            Ok(estimator) => return Ok(estimator),
+            Err(e) => {
+                println!("⚠️ {:?} backend failed: {}", backend, e);
+                errors.push(format!("{:?}: {}", backend, e));
+            }
+        }
+    }
+    Err(errors.join("; "))
+}
+
+fn load_with(backend: Backend, path: &Path, entry: &ModelEntry, config: &ModelConfig) -> Result<Box<dyn PoseEstimator>, String> {
+    match backend {
+        #[cfg(feature = "backend-ort")]
+        Backend::Ort => Ok(Box::new(super::ort_backend::OrtEstimator::load(path, entry, config)?)),
+        #[cfg(feature = "backend-tract")]
+        Backend::Tract => Ok(Box::new(super::tract_backend::TractEstimator::load(path, entry, config)?)),
+        #[allow(unreachable_patterns)]
+        other => Err(format!("{:?} backend is not compiled into this build", other)),
+    }
+}
+
+/// Reads the declared input the same way for every backend and checks it against the manifest.
+pub(super) fn negotiate_input(name: &str, shape: &[i64], dtype: InputDType, entry: &ModelEntry, config: &ModelConfig) -> Result<InputSpec, String> {
+    let input = InputSpec::negotiate(name, shape, dtype, &config.input)?;
+
+    // The manifest and the graph must agree, otherwise the manifest is for another file
+    if let Some(expected) = entry.input_layout.as_deref() {
+        if !format!("{:?}", input.layout).eq_ignore_ascii_case(expected) {
+            return Err(format!("Manifest says {} input is {}, model declares {:?}",
+                entry.version_tag(), expected, input.layout));
+        }
+    }
+    Ok(input)
+}
+
+/// Backend-independent halves of an inference: image -> tensor data, and raw output -> poses.
+pub(super) struct TensorPipeline {
+    pub input: InputSpec,
+    pub decoder: PoseDecoder,
+    /// Index of the metric world-landmark output, if the model has one (BlazePose only).
+    pub world_output: Option<usize>,
+}
+
+impl TensorPipeline {
+    /// Picks the output parser from the manifest, then model metadata, then the shape of the
+    /// first output. `output_shapes` lists every model output, in order.
+    pub fn new(input: InputSpec, output_shapes: &[Vec<i64>], entry: &ModelEntry, metadata_hint: Option<String>) -> Result<Self, String> {
+        let layout_hint = entry.output_layout.clone().or(metadata_hint);
+        let output_shape = output_shapes.first().map(Vec::as_slice).unwrap_or_default();
+        let decoder = PoseDecoder::from_output(output_shape, layout_hint.as_deref())
+            .map_err(|e| e.to_string())?;
+        let world_output = match decoder {
+            PoseDecoder::BlazePose => output_shapes.iter().skip(1).position(|s| is_world_output(s)).map(|i| i + 1),
+            _ => None,
+        };
+        Ok(Self { input, decoder, world_output })
+    }
+
+    // Aspect ratio is preserved so ear/shoulder x-distances are not squashed on 16:9 cameras
+    pub fn prepare(&self, img: &RgbImage) -> (Letterbox, InputData) {
+        let letterbox = Letterbox::fit(img.dimensions(), (self.input.width, self.input.height), self.input.resize_mode);
+        let resized = letterbox.apply(img);
+        (letterbox, self.input.build(&resized))
+    }
+
+    /// Decodes the pose output and, if given, the world output. World landmarks are metric
+    /// and hip-centred, so the letterbox does not apply to them.
+    pub fn finish(&self, data: &[f32], world: Option<&[f32]>, letterbox: &Letterbox) -> Result<Vec<DecodedPose>, String> {
+        let mut poses = self.decoder.decode(data, (self.input.width, self.input.height))
+            .map_err(|e| e.to_string())?;
+        for pose in poses.iter_mut() {
+            if let Some(world) = world {
+                pose.world = Some(decode_world(world, &pose.landmarks).map_err(|e| e.to_string())?);
+            }
+            for lm in pose.landmarks.iter_mut() {
+                letterbox.unproject(lm);
+            }
+        }
+        Ok(poses)
+    }
+
+    pub fn describe(&self, backend: &str) -> String {
+        format!("{} {}x{} {:?} {:?} {}{}", backend, self.input.width, self.input.height,
+            self.input.layout, self.input.dtype, self.decoder.name(),
+            if self.world_output.is_some() { "+world" } else { "" })
+    }
+}
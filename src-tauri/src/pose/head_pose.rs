@@ -0,0 +1,165 @@
+use serde::Serialize;
+use super::keypoints::*;
+use super::Landmark;
+
+// Nose, both eyes and one ear: fewer points, or only the coplanar ones, cannot fix a rotation
+const MIN_POINTS: usize = 4;
+
+/// Canonical adult head in centimetres, facing the camera: x right in the image, y down,
+/// z away from the camera, origin between the ears. Only the shape matters for the fit;
+/// the scale is solved per frame.
+const HEAD_MODEL: [(usize, [f32; 3]); 5] = [
+    (NOSE, [0.0, 2.5, -10.5]),
+    (LEFT_EYE, [3.2, -1.5, -8.0]),
+    (RIGHT_EYE, [-3.2, -1.5, -8.0]),
+    (LEFT_EAR, [7.5, 0.0, 0.0]),
+    (RIGHT_EAR, [-7.5, 0.0, 0.0]),
+];
+
+/// Head orientation in degrees relative to facing the camera squarely.
+///   pitch: positive when looking down (text neck)
+///   yaw:   positive when turned towards the user's right (the image's left edge)
+///   roll:  positive when tilted towards the user's left shoulder
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct HeadPose {
+    pub pitch_deg: f32,
+    pub yaw_deg: f32,
+    pub roll_deg: f32,
+}
+
+impl HeadPose {
+    /// Fits the canonical head to the face keypoints. `aspect` is frame width / height,
+    /// needed because landmarks are normalised per axis.
+    pub fn estimate(landmarks: &[Landmark], aspect: f32) -> Option<Self> {
+        let points: Vec<([f32; 3], [f32; 2], f32)> = HEAD_MODEL.iter()
+            .filter_map(|&(i, model)| {
+                let lm = visible(landmarks, i)?;
+                Some((model, [lm.x * aspect, lm.y], lm.visibility))
+            })
+            .collect();
+        if points.len() < MIN_POINTS {
+            return None;
+        }
+        fit(&points).map(|r| Self::from_rotation(&r))
+    }
+
+    // R = Rz(roll) * Ry(yaw) * Rx(pitch)
+    fn from_rotation(r: &[[f32; 3]; 3]) -> Self {
+        Self {
+            pitch_deg: r[2][1].atan2(r[2][2]).to_degrees(),
+            yaw_deg: (-r[2][0]).clamp(-1.0, 1.0).asin().to_degrees(),
+            roll_deg: r[1][0].atan2(r[0][0]).to_degrees(),
+        }
+    }
+}
+
+/// Weak-perspective PnP: least-squares 2x3 projection from model to image points (weighted
+/// by visibility), then the nearest rotation. A head is small next to its distance from a
+/// webcam, so ignoring perspective costs little and needs no camera intrinsics. A face-mesh
+/// model could feed more correspondences through the same fit.
+fn fit(points: &[([f32; 3], [f32; 2], f32)]) -> Option<[[f32; 3]; 3]> {
+    let total: f32 = points.iter().map(|p| p.2).sum();
+    let mut model_mean = [0.0f32; 3];
+    let mut image_mean = [0.0f32; 2];
+    for (model, image, w) in points {
+        (0..3).for_each(|k| model_mean[k] += w * model[k] / total);
+        (0..2).for_each(|k| image_mean[k] += w * image[k] / total);
+    }
+
+    // A = sum w x X^T (2x3), B = sum w X X^T (3x3), M = A B^-1
+    let mut a = [[0.0f32; 3]; 2];
+    let mut b = [[0.0f32; 3]; 3];
+    for (model, image, w) in points {
+        let x = [model[0] - model_mean[0], model[1] - model_mean[1], model[2] - model_mean[2]];
+        let u = [image[0] - image_mean[0], image[1] - image_mean[1]];
+        for i in 0..3 {
+            for j in 0..3 { b[i][j] += w * x[i] * x[j]; }
+            for (row, &ui) in a.iter_mut().zip(&u) { row[i] += w * ui * x[i]; }
+        }
+    }
+    let b_inv = invert(&b)?;
+    let m: Vec<[f32; 3]> = a.iter()
+        .map(|row| [0, 1, 2].map(|j| (0..3).map(|k| row[k] * b_inv[k][j]).sum::<f32>()))
+        .collect();
+
+    // Orthonormalise the two projection rows; the third axis follows from them
+    let r1 = normalize(m[0])?;
+    let d = dot(r1, m[1]);
+    let r2 = normalize([m[1][0] - d * r1[0], m[1][1] - d * r1[1], m[1][2] - d * r1[2]])?;
+    Some([r1, r2, cross(r1, r2)])
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn normalize(v: [f32; 3]) -> Option<[f32; 3]> {
+    let n = dot(v, v).sqrt();
+    (n > f32::EPSILON).then(|| [v[0] / n, v[1] / n, v[2] / n])
+}
+
+fn invert(m: &[[f32; 3]; 3]) -> Option<[[f32; 3]; 3]> {
+    let c = |r1: usize, c1: usize, r2: usize, c2: usize| m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1];
+    let cof = [
+        [c(1, 1, 2, 2), -c(1, 0, 2, 2), c(1, 0, 2, 1)],
+        [-c(0, 1, 2, 2), c(0, 0, 2, 2), -c(0, 0, 2, 1)],
+        [c(0, 1, 1, 2), -c(0, 0, 1, 2), c(0, 0, 1, 1)],
+    ];
+    let det = m[0][0] * cof[0][0] + m[0][1] * cof[0][1] + m[0][2] * cof[0][2];
+    if det.abs() < 1e-6 {
+        return None;
+    }
+    // Inverse is the transposed cofactor matrix over the determinant
+    Some([0, 1, 2].map(|i| [0, 1, 2].map(|j| cof[j][i] / det)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Projects the head model rotated by R = Rz * Ry * Rx into normalised image coordinates
+    fn project(pitch: f32, yaw: f32, roll: f32) -> Vec<Landmark> {
+        let (sp, cp) = pitch.to_radians().sin_cos();
+        let (sy, cy) = yaw.to_radians().sin_cos();
+        let (sr, cr) = roll.to_radians().sin_cos();
+        let rx = |p: [f32; 3]| [p[0], cp * p[1] - sp * p[2], sp * p[1] + cp * p[2]];
+        let ry = |p: [f32; 3]| [cy * p[0] + sy * p[2], p[1], -sy * p[0] + cy * p[2]];
+        let rz = |p: [f32; 3]| [cr * p[0] - sr * p[1], sr * p[0] + cr * p[1], p[2]];
+
+        let mut landmarks = vec![Landmark { x: 0.0, y: 0.0, z: 0.0, visibility: 0.0 }; LANDMARK_COUNT];
+        for &(i, model) in HEAD_MODEL.iter() {
+            let p = rz(ry(rx(model)));
+            landmarks[i] = Landmark { x: 0.5 + p[0] * 0.01, y: 0.3 + p[1] * 0.01, z: 0.0, visibility: 0.9 };
+        }
+        landmarks
+    }
+
+    #[test]
+    fn recovers_the_rotation_it_was_given() {
+        for &(pitch, yaw, roll) in &[(0.0, 0.0, 0.0), (20.0, 0.0, 0.0), (-10.0, 25.0, 5.0), (15.0, -20.0, -10.0)] {
+            let pose = HeadPose::estimate(&project(pitch, yaw, roll), 1.0).unwrap();
+            assert!((pose.pitch_deg - pitch).abs() < 0.5, "pitch {} vs {}", pose.pitch_deg, pitch);
+            assert!((pose.yaw_deg - yaw).abs() < 0.5, "yaw {} vs {}", pose.yaw_deg, yaw);
+            assert!((pose.roll_deg - roll).abs() < 0.5, "roll {} vs {}", pose.roll_deg, roll);
+        }
+    }
+
+    #[test]
+    fn looking_down_is_positive_pitch() {
+        let pose = HeadPose::estimate(&project(30.0, 0.0, 0.0), 1.0).unwrap();
+        assert!(pose.pitch_deg > 25.0);
+    }
+
+    #[test]
+    fn needs_enough_of_the_face() {
+        let mut landmarks = project(0.0, 0.0, 0.0);
+        landmarks[LEFT_EAR].visibility = 0.1;
+        assert!(HeadPose::estimate(&landmarks, 1.0).is_some());
+        landmarks[RIGHT_EAR].visibility = 0.1;
+        assert!(HeadPose::estimate(&landmarks, 1.0).is_none());
+    }
+}
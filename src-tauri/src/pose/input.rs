@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use image::RgbImage;
+use super::letterbox::ResizeMode;
+
+// Used when the model declares a dynamic (-1) spatial dimension and no override is set
+const DEFAULT_INPUT_SIZE: u32 = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TensorLayout { Nhwc, Nchw }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputDType { F32, U8, I32 }
+
+/// Per-channel `(pixel - mean) / std`, applied to float inputs only.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Normalization {
+    pub mean: [f32; 3],
+    pub std: [f32; 3],
+}
+
+impl Normalization {
+    /// Plain 0..1 scaling, what the original BlazePose export expects.
+    pub const UNIT: Normalization = Normalization { mean: [0.0; 3], std: [255.0; 3] };
+}
+
+/// Input overrides read from the settings table, so swapping models needs no code change.
+///   model_input_mean / model_input_std: "r,g,b" (e.g. "127.5,127.5,127.5"); std must be positive
+///   model_input_size: square size used when the model input is dynamic
+///   model_resize_mode: "letterbox" (default), "crop" or "stretch"
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputConfig {
+    pub normalization: Option<Normalization>,
+    pub input_size: Option<u32>,
+    pub resize_mode: ResizeMode,
+}
+
+impl InputConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let triple = |key: &str| -> Option<[f32; 3]> {
+            let values: Vec<f32> = settings.get(key)?.split(',').filter_map(|v| v.trim().parse().ok()).collect();
+            match values.as_slice() {
+                [v] => Some([*v; 3]),
+                [r, g, b] => Some([*r, *g, *b]),
+                _ => None,
+            }
+        };
+
+        // A zero (or negative) std would divide the tensor into inf/NaN; keep the default instead
+        let std = triple("model_input_std").filter(|std| std.iter().all(|v| v.is_finite() && *v > 0.0));
+        let normalization = match (triple("model_input_mean"), std) {
+            (None, None) => None,
+            (mean, std) => Some(Normalization {
+                mean: mean.unwrap_or(Normalization::UNIT.mean),
+                std: std.unwrap_or(Normalization::UNIT.std),
+            }),
+        };
+
+        Self {
+            normalization,
+            input_size: settings.get("model_input_size").and_then(|v| v.parse().ok()),
+            resize_mode: settings.get("model_resize_mode").and_then(|v| ResizeMode::parse(v)).unwrap_or_default(),
+        }
+    }
+}
+
+/// Typed tensor payload ready to hand to the runtime.
+pub enum InputData {
+    F32(Vec<f32>),
+    U8(Vec<u8>),
+    I32(Vec<i32>),
+}
+
+/// What the loaded model wants as input, negotiated from `session.inputs()` at load time.
+#[derive(Debug, Clone)]
+pub struct InputSpec {
+    pub name: String,
+    pub layout: TensorLayout,
+    pub width: u32,
+    pub height: u32,
+    pub dtype: InputDType,
+    pub normalization: Normalization,
+    pub resize_mode: ResizeMode,
+}
+
+impl InputSpec {
+    pub fn negotiate(name: &str, shape: &[i64], dtype: InputDType, config: &InputConfig) -> Result<Self, String> {
+        let [_, a, b, c] = shape else {
+            return Err(format!("Unsupported input rank {:?}: expected a 4D image tensor", shape));
+        };
+
+        let size = |dim: i64| -> u32 {
+            if dim > 0 { dim as u32 } else { config.input_size.unwrap_or(DEFAULT_INPUT_SIZE) }
+        };
+
+        let (layout, height, width) = match (*a, *c) {
+            (_, 3) => (TensorLayout::Nhwc, size(*a), size(*b)),
+            (3, _) => (TensorLayout::Nchw, size(*b), size(*c)),
+            _ => return Err(format!("Cannot find a 3-channel axis in input shape {:?}", shape)),
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            layout,
+            width,
+            height,
+            dtype,
+            normalization: config.normalization.unwrap_or(Normalization::UNIT),
+            resize_mode: config.resize_mode,
+        })
+    }
+
+    pub fn shape(&self) -> [usize; 4] {
+        let (h, w) = (self.height as usize, self.width as usize);
+        match self.layout {
+            TensorLayout::Nhwc => [1, h, w, 3],
+            TensorLayout::Nchw => [1, 3, h, w],
+        }
+    }
+
+    /// Lays out an already-resized RGB image in the negotiated order and element type.
+    /// Integer inputs (e.g. MoveNet's int32) take raw 0..255 values and skip normalisation.
+    pub fn build(&self, img: &RgbImage) -> InputData {
+        let (w, h) = (self.width as usize, self.height as usize);
+        let raw = img.as_raw();
+
+        let index = |i: usize| -> usize {
+            match self.layout {
+                TensorLayout::Nhwc => i,
+                // i walks HWC; remap to CHW
+                TensorLayout::Nchw => (i % 3) * w * h + i / 3,
+            }
+        };
+
+        match self.dtype {
+            InputDType::F32 => {
+                let n = self.normalization;
+                let mut data = vec![0.0f32; raw.len()];
+                for (i, &p) in raw.iter().enumerate() {
+                    data[index(i)] = (p as f32 - n.mean[i % 3]) / n.std[i % 3];
+                }
+                InputData::F32(data)
+            }
+            InputDType::U8 => {
+                let mut data = vec![0u8; raw.len()];
+                for (i, &p) in raw.iter().enumerate() { data[index(i)] = p; }
+                InputData::U8(data)
+            }
+            InputDType::I32 => {
+                let mut data = vec![0i32; raw.len()];
+                for (i, &p) in raw.iter().enumerate() { data[index(i)] = p as i32; }
+                InputData::I32(data)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn layout_comes_from_the_channel_axis() {
+        let config = InputConfig::default();
+        let nhwc = InputSpec::negotiate("input", &[1, 192, 256, 3], InputDType::I32, &config).unwrap();
+        assert_eq!((nhwc.layout, nhwc.height, nhwc.width), (TensorLayout::Nhwc, 192, 256));
+        assert_eq!(nhwc.shape(), [1, 192, 256, 3]);
+
+        let nchw = InputSpec::negotiate("input", &[1, 3, 224, 320], InputDType::F32, &config).unwrap();
+        assert_eq!((nchw.layout, nchw.height, nchw.width), (TensorLayout::Nchw, 224, 320));
+        assert_eq!(nchw.shape(), [1, 3, 224, 320]);
+    }
+
+    #[test]
+    fn dynamic_dimensions_use_the_configured_size() {
+        let spec = InputSpec::negotiate("input", &[-1, -1, -1, 3], InputDType::F32, &InputConfig::default()).unwrap();
+        assert_eq!((spec.height, spec.width), (DEFAULT_INPUT_SIZE, DEFAULT_INPUT_SIZE));
+
+        let config = InputConfig::from_settings(&settings(&[("model_input_size", "320")]));
+        let spec = InputSpec::negotiate("input", &[1, 3, -1, 192], InputDType::F32, &config).unwrap();
+        assert_eq!((spec.height, spec.width), (320, 192));
+    }
+
+    #[test]
+    fn non_image_inputs_are_rejected() {
+        let config = InputConfig::default();
+        assert!(InputSpec::negotiate("input", &[1, 256, 3], InputDType::F32, &config).is_err());
+        assert!(InputSpec::negotiate("input", &[1, 4, 256, 256], InputDType::F32, &config).is_err());
+    }
+
+    #[test]
+    fn non_positive_std_falls_back_to_the_default() {
+        let config = InputConfig::from_settings(&settings(&[("model_input_mean", "127.5"), ("model_input_std", "0")]));
+        assert_eq!(config.normalization, Some(Normalization { mean: [127.5; 3], std: Normalization::UNIT.std }));
+
+        let config = InputConfig::from_settings(&settings(&[("model_input_std", "1,-1,1")]));
+        assert_eq!(config.normalization, None);
+
+        let config = InputConfig::from_settings(&settings(&[("model_input_std", "58.4,57.1,57.4")]));
+        assert_eq!(config.normalization.unwrap().std, [58.4, 57.1, 57.4]);
+    }
+}
@@ -0,0 +1,70 @@
+// Canonical landmark topology.
+// Every decoder emits the 33-point BlazePose layout so the rest of the app can
+// index landmarks the same way regardless of which model produced them.
+
+use super::Landmark;
+
+pub const LANDMARK_COUNT: usize = 33;
+
+pub const NOSE: usize = 0;
+pub const LEFT_EYE: usize = 2;
+pub const RIGHT_EYE: usize = 5;
+pub const LEFT_EAR: usize = 7;
+pub const RIGHT_EAR: usize = 8;
+pub const LEFT_SHOULDER: usize = 11;
+pub const RIGHT_SHOULDER: usize = 12;
+pub const LEFT_ELBOW: usize = 13;
+pub const RIGHT_ELBOW: usize = 14;
+pub const LEFT_WRIST: usize = 15;
+pub const RIGHT_WRIST: usize = 16;
+pub const LEFT_HIP: usize = 23;
+pub const RIGHT_HIP: usize = 24;
+pub const LEFT_KNEE: usize = 25;
+pub const RIGHT_KNEE: usize = 26;
+pub const LEFT_ANKLE: usize = 27;
+pub const RIGHT_ANKLE: usize = 28;
+
+/// COCO-17 (MoveNet) keypoint order mapped onto BlazePose slots.
+pub const COCO_TO_BLAZEPOSE: [usize; 17] = [
+    NOSE,
+    LEFT_EYE,
+    RIGHT_EYE,
+    LEFT_EAR,
+    RIGHT_EAR,
+    LEFT_SHOULDER,
+    RIGHT_SHOULDER,
+    LEFT_ELBOW,
+    RIGHT_ELBOW,
+    LEFT_WRIST,
+    RIGHT_WRIST,
+    LEFT_HIP,
+    RIGHT_HIP,
+    LEFT_KNEE,
+    RIGHT_KNEE,
+    LEFT_ANKLE,
+    RIGHT_ANKLE,
+];
+
+/// Below this visibility a landmark is the model guessing (e.g. hips under the desk).
+pub const MIN_VISIBILITY: f32 = 0.5;
+
+/// The landmark at `i`, if it is confidently visible.
+pub fn visible(landmarks: &[Landmark], i: usize) -> Option<&Landmark> {
+    landmarks.get(i).filter(|lm| lm.visibility >= MIN_VISIBILITY)
+}
+
+/// Where the camera sees the torso and how big, normalised.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShoulderFrame {
+    pub mid_x: f32,
+    pub mid_y: f32,
+    /// Never zero, so it can be divided by.
+    pub width: f32,
+}
+
+/// None unless both shoulders are visible.
+pub fn shoulder_frame(landmarks: &[Landmark]) -> Option<ShoulderFrame> {
+    let (l, r) = (visible(landmarks, LEFT_SHOULDER)?, visible(landmarks, RIGHT_SHOULDER)?);
+    let width = ((l.x - r.x).powi(2) + (l.y - r.y).powi(2)).sqrt();
+    Some(ShoulderFrame { mid_x: (l.x + r.x) / 2.0, mid_y: (l.y + r.y) / 2.0, width: width.max(f32::EPSILON) })
+}
@@ -0,0 +1,128 @@
+use image::{imageops, imageops::FilterType, Rgb, RgbImage};
+use super::Landmark;
+
+/// How a camera frame is fitted into the square model input.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ResizeMode {
+    /// Keep aspect ratio, pad the short side. Nothing is lost, nothing is squashed.
+    #[default]
+    Letterbox,
+    /// Keep aspect ratio, cut the long side. More pixels on the user, edges are dropped.
+    CenterCrop,
+    /// Legacy `resize_exact` behaviour. Distorts horizontal distances on 16:9 cameras.
+    Stretch,
+}
+
+impl ResizeMode {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "letterbox" => Some(ResizeMode::Letterbox),
+            "crop" => Some(ResizeMode::CenterCrop),
+            "stretch" => Some(ResizeMode::Stretch),
+            _ => None,
+        }
+    }
+}
+
+/// Maps source pixels into model input pixels: `dst = src * scale + offset`.
+/// Kept per frame so landmarks can be projected back into source-frame space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Letterbox {
+    src: (f32, f32),
+    dst: (f32, f32),
+    scale: (f32, f32),
+    offset: (f32, f32),
+}
+
+impl Letterbox {
+    pub fn fit(src: (u32, u32), dst: (u32, u32), mode: ResizeMode) -> Self {
+        let (sw, sh) = (src.0 as f32, src.1 as f32);
+        let (dw, dh) = (dst.0 as f32, dst.1 as f32);
+
+        let scale = match mode {
+            ResizeMode::Letterbox => { let s = (dw / sw).min(dh / sh); (s, s) }
+            ResizeMode::CenterCrop => { let s = (dw / sw).max(dh / sh); (s, s) }
+            ResizeMode::Stretch => (dw / sw, dh / sh),
+        };
+        let offset = ((dw - sw * scale.0) / 2.0, (dh - sh * scale.1) / 2.0);
+
+        Self { src: (sw, sh), dst: (dw, dh), scale, offset }
+    }
+
+    /// Produces the model-sized image, padding with black where the frame does not reach.
+    pub fn apply(&self, img: &RgbImage) -> RgbImage {
+        let resized_w = (self.src.0 * self.scale.0).round().max(1.0) as u32;
+        let resized_h = (self.src.1 * self.scale.1).round().max(1.0) as u32;
+        let resized = imageops::resize(img, resized_w, resized_h, FilterType::Triangle);
+
+        let mut canvas = RgbImage::from_pixel(self.dst.0 as u32, self.dst.1 as u32, Rgb([0, 0, 0]));
+        imageops::overlay(&mut canvas, &resized, self.offset.0.round() as i64, self.offset.1.round() as i64);
+        canvas
+    }
+
+    /// Model-normalised landmark -> source-frame-normalised landmark.
+    pub fn unproject(&self, lm: &mut Landmark) {
+        let px = lm.x * self.dst.0;
+        let py = lm.y * self.dst.1;
+        lm.x = (px - self.offset.0) / self.scale.0 / self.src.0;
+        lm.y = (py - self.offset.1) / self.scale.1 / self.src.1;
+        // z shares the x scale in BlazePose
+        lm.z = lm.z * self.dst.0 / self.scale.0 / self.src.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MODEL: (u32, u32) = (256, 256);
+
+    fn unproject(lb: &Letterbox, x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+        let mut lm = Landmark { x, y, z, visibility: 1.0 };
+        lb.unproject(&mut lm);
+        (lm.x, lm.y, lm.z)
+    }
+
+    fn assert_near(got: (f32, f32, f32), want: (f32, f32, f32)) {
+        let close = (got.0 - want.0).abs() < 1e-5 && (got.1 - want.1).abs() < 1e-5 && (got.2 - want.2).abs() < 1e-5;
+        assert!(close, "got {:?}, want {:?}", got, want);
+    }
+
+    #[test]
+    fn letterbox_pads_the_short_side() {
+        // 16:9 lands in a 256x144 band, 56 px from the top
+        let lb = Letterbox::fit((1280, 720), MODEL, ResizeMode::Letterbox);
+        assert_near(unproject(&lb, 0.5, 0.21875, 0.1), (0.5, 0.0, 0.1));
+        assert_near(unproject(&lb, 0.25, 0.5, 0.0), (0.25, 0.5, 0.0));
+        assert_near(unproject(&lb, 1.0, 0.78125, 0.0), (1.0, 1.0, 0.0));
+
+        // 4:3 lands in 256x192, 32 px from the top
+        let lb = Letterbox::fit((640, 480), MODEL, ResizeMode::Letterbox);
+        assert_near(unproject(&lb, 0.5, 0.125, 0.0), (0.5, 0.0, 0.0));
+        assert_near(unproject(&lb, 1.0, 0.875, 0.0), (1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn center_crop_cuts_the_long_side() {
+        // 16:9 is scaled to 455x256; the model's left edge is 280 source px in
+        let lb = Letterbox::fit((1280, 720), MODEL, ResizeMode::CenterCrop);
+        assert_near(unproject(&lb, 0.0, 0.0, 0.1), (0.21875, 0.0, 0.05625));
+        assert_near(unproject(&lb, 0.5, 0.5, 0.0), (0.5, 0.5, 0.0));
+        assert_near(unproject(&lb, 1.0, 1.0, 0.0), (0.78125, 1.0, 0.0));
+
+        // 4:3 is scaled to 341x256, 80 source px cut each side
+        let lb = Letterbox::fit((640, 480), MODEL, ResizeMode::CenterCrop);
+        assert_near(unproject(&lb, 0.0, 0.0, 0.0), (0.125, 0.0, 0.0));
+        assert_near(unproject(&lb, 1.0, 1.0, 0.0), (0.875, 1.0, 0.0));
+    }
+
+    #[test]
+    fn stretch_maps_corners_to_corners() {
+        for src in [(1280, 720), (640, 480)] {
+            let lb = Letterbox::fit(src, MODEL, ResizeMode::Stretch);
+            assert_near(unproject(&lb, 0.0, 0.0, 0.0), (0.0, 0.0, 0.0));
+            assert_near(unproject(&lb, 0.3, 0.7, 0.0), (0.3, 0.7, 0.0));
+            assert_near(unproject(&lb, 1.0, 1.0, 0.0), (1.0, 1.0, 0.0));
+        }
+    }
+}
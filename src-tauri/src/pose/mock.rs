@@ -0,0 +1,120 @@
+//! Test doubles for the engine: run the pose path without a model file or onnxruntime.
+
+use std::sync::{Arc, Mutex};
+use image::RgbImage;
+use super::decoder::DecodedPose;
+use super::estimator::{negotiate_input, PoseEstimator, TensorPipeline};
+use super::input::{InputData, InputDType};
+use super::keypoints::*;
+use super::{InputConfig, Landmark, ModelConfig, ModelEntry};
+use super::letterbox::ResizeMode;
+
+pub fn test_entry(name: &str) -> ModelEntry {
+    ModelEntry {
+        name: name.to_string(),
+        version: "test".to_string(),
+        file: format!("{}.onnx", name),
+        input_layout: None,
+        output_layout: None,
+        sha256: String::new(),
+    }
+}
+
+/// Returns scripted poses, one script entry per call, repeating the last one when it runs out.
+/// Records the size of every image it is handed so ROI cropping can be checked.
+pub struct MockEstimator {
+    script: Vec<Vec<DecodedPose>>,
+    next: usize,
+    pub seen: Arc<Mutex<Vec<(u32, u32)>>>,
+}
+
+impl MockEstimator {
+    pub fn new(script: Vec<Vec<DecodedPose>>) -> Self {
+        Self { script, next: 0, seen: Arc::new(Mutex::new(Vec::new())) }
+    }
+
+    /// Always reports the same single person.
+    pub fn constant(landmarks: Vec<Landmark>) -> Self {
+        Self::new(vec![vec![DecodedPose { landmarks, score: 0.9, world: None }]])
+    }
+}
+
+impl PoseEstimator for MockEstimator {
+    fn describe(&self) -> String {
+        format!("mock ({} scripted frames)", self.script.len())
+    }
+
+    fn estimate(&mut self, img: &RgbImage) -> Result<Vec<DecodedPose>, String> {
+        self.seen.lock().unwrap().push(img.dimensions());
+        let poses = self.script.get(self.next).or(self.script.last()).cloned().unwrap_or_default();
+        self.next += 1;
+        Ok(poses)
+    }
+}
+
+/// Real preprocessing and decoding around a scripted output tensor: everything but the runtime.
+pub struct FixtureEstimator {
+    pipeline: TensorPipeline,
+    output: Vec<f32>,
+    /// Mean of the last input tensor built, to check letterboxing and normalisation.
+    pub input_mean: Arc<Mutex<Option<f64>>>,
+}
+
+impl FixtureEstimator {
+    pub fn new(input_shape: &[i64], dtype: InputDType, output_shape: &[i64], output_layout: Option<String>,
+               resize_mode: ResizeMode, output: Vec<f32>) -> Result<Self, String> {
+        let config = ModelConfig {
+            input: InputConfig { resize_mode, ..InputConfig::default() },
+            ..ModelConfig::default()
+        };
+        let entry = ModelEntry { output_layout, ..test_entry("fixture") };
+        let input = negotiate_input("input", input_shape, dtype, &entry, &config)?;
+        let pipeline = TensorPipeline::new(input, &[output_shape.to_vec()], &entry, None)?;
+        Ok(Self { pipeline, output, input_mean: Arc::new(Mutex::new(None)) })
+    }
+}
+
+impl PoseEstimator for FixtureEstimator {
+    fn describe(&self) -> String {
+        self.pipeline.describe("fixture")
+    }
+
+    fn estimate(&mut self, img: &RgbImage) -> Result<Vec<DecodedPose>, String> {
+        let (letterbox, data) = self.pipeline.prepare(img);
+        let expected_len: usize = self.pipeline.input.shape().iter().product();
+        let (len, sum) = match &data {
+            InputData::F32(d) => (d.len(), d.iter().map(|&v| v as f64).sum::<f64>()),
+            InputData::U8(d) => (d.len(), d.iter().map(|&v| v as f64).sum::<f64>()),
+            InputData::I32(d) => (d.len(), d.iter().map(|&v| v as f64).sum::<f64>()),
+        };
+        if len != expected_len {
+            return Err(format!("Input tensor has {} values, shape needs {}", len, expected_len));
+        }
+        *self.input_mean.lock().unwrap() = Some(sum / len as f64);
+
+        self.pipeline.finish(&self.output, None, &letterbox)
+    }
+}
+
+/// A user sitting upright in front of the camera, landmarks normalised to the frame.
+/// Head and torso are confident, legs are under the desk.
+pub fn seated_user(center_x: f32) -> Vec<Landmark> {
+    let mut landmarks = vec![Landmark { x: center_x, y: 0.9, z: 0.0, visibility: 0.1 }; LANDMARK_COUNT];
+    let mut set = |i: usize, dx: f32, y: f32| {
+        landmarks[i] = Landmark { x: center_x + dx, y, z: 0.0, visibility: 0.95 };
+    };
+    set(NOSE, 0.0, 0.30);
+    set(LEFT_EYE, 0.03, 0.27);
+    set(RIGHT_EYE, -0.03, 0.27);
+    set(LEFT_EAR, 0.06, 0.29);
+    set(RIGHT_EAR, -0.06, 0.29);
+    set(LEFT_SHOULDER, 0.15, 0.50);
+    set(RIGHT_SHOULDER, -0.15, 0.50);
+    set(LEFT_ELBOW, 0.19, 0.70);
+    set(RIGHT_ELBOW, -0.19, 0.70);
+    set(LEFT_WRIST, 0.12, 0.82);
+    set(RIGHT_WRIST, -0.12, 0.82);
+    set(LEFT_HIP, 0.10, 0.92);
+    set(RIGHT_HIP, -0.10, 0.92);
+    landmarks
+}
@@ -0,0 +1,94 @@
+use std::path::Path;
+use image::RgbImage;
+use ort::session::Session;
+use ort::tensor::TensorElementType;
+use ort::value::Tensor;
+use super::decoder::{DecodedPose, LAYOUT_METADATA_KEY};
+use super::estimator::{negotiate_input, PoseEstimator, TensorPipeline};
+use super::input::{InputData, InputDType};
+use super::{ModelConfig, ModelEntry};
+
+/// ONNX Runtime backend (`load-dynamic`: the shared library is found at run time).
+pub struct OrtEstimator {
+    session: Session,
+    pipeline: TensorPipeline,
+    output_name: String,
+    world_output_name: Option<String>,
+}
+
+impl OrtEstimator {
+    pub fn load(path: &Path, entry: &ModelEntry, config: &ModelConfig) -> Result<Self, String> {
+        let builder = Session::builder().map_err(|e| format!("Builder error: {}", e))?;
+        let session = config.runtime.apply(builder)?
+            .commit_from_file(path)
+            .map_err(|e| format!("Load error: {}", e))?;
+
+        // Read the input layout/dtype the model declares instead of assuming 256x256 f32 NHWC
+        let input_outlet = &session.inputs()[0];
+        let input_dtype = match input_outlet.dtype().tensor_type() {
+            Some(TensorElementType::Float32) => InputDType::F32,
+            Some(TensorElementType::Uint8) => InputDType::U8,
+            Some(TensorElementType::Int32) => InputDType::I32,
+            other => return Err(format!("Unsupported input element type: {:?}", other)),
+        };
+        let input_shape: Vec<i64> = input_outlet.dtype().tensor_shape()
+            .map(|shape| shape.to_vec())
+            .unwrap_or_default();
+        let input = negotiate_input(input_outlet.name(), &input_shape, input_dtype, entry, config)?;
+
+        let metadata_hint = session.metadata().ok().and_then(|m| m.custom(LAYOUT_METADATA_KEY));
+        let output_shapes: Vec<Vec<i64>> = session.outputs().iter()
+            .map(|outlet| outlet.dtype().tensor_shape().map(|shape| shape.to_vec()).unwrap_or_default())
+            .collect();
+        let output_name = session.outputs()[0].name().to_string();
+        let pipeline = TensorPipeline::new(input, &output_shapes, entry, metadata_hint)?;
+        // BlazePose also emits metric world landmarks on a later output
+        let world_output_name = pipeline.world_output.map(|i| session.outputs()[i].name().to_string());
+
+        Ok(Self { session, pipeline, output_name, world_output_name })
+    }
+}
+
+impl PoseEstimator for OrtEstimator {
+    fn describe(&self) -> String {
+        self.pipeline.describe("ort")
+    }
+
+    fn estimate(&mut self, img: &RgbImage) -> Result<Vec<DecodedPose>, String> {
+        // 1. Preprocessing (Fit, Lay out & Normalize)
+        let (letterbox, data) = self.pipeline.prepare(img);
+        let shape = self.pipeline.input.shape();
+        let input_tensor = match data {
+            InputData::F32(data) => Tensor::from_array((shape, data)).map(|t| t.upcast()),
+            InputData::U8(data) => Tensor::from_array((shape, data)).map(|t| t.upcast()),
+            InputData::I32(data) => Tensor::from_array((shape, data)).map(|t| t.upcast()),
+        }.map_err(|e| {
+            println!("❌ Tensor Creation Failed: {}", e);
+            format!("Tensor creation failed: {}", e)
+        })?;
+
+        // 2. Run Inference
+        let inputs = ort::inputs![self.pipeline.input.name.as_str() => input_tensor];
+        let outputs = self.session.run(inputs)
+            .map_err(|e| {
+                println!("❌ Session Run Failed: {}", e);
+                format!("Inference failed: {}", e)
+            })?;
+
+        // 3. Extract Output
+        // try_extract_tensor returns (Shape, DataSlice) in v2.0
+        let (_, data) = outputs[self.output_name.as_str()].try_extract_tensor::<f32>()
+            .map_err(|e| {
+                println!("❌ Tensor Extraction Failed: {}", e);
+                format!("Extraction failed: {}", e)
+            })?;
+        let world = match &self.world_output_name {
+            Some(name) => Some(outputs[name.as_str()].try_extract_tensor::<f32>()
+                .map_err(|e| format!("Extraction failed: {}", e))?.1),
+            None => None,
+        };
+
+        // 4. Decode with the layout negotiated at load time (one pose per detected person)
+        self.pipeline.finish(data, world, &letterbox)
+    }
+}
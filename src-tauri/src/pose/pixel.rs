@@ -0,0 +1,304 @@
+use image::{ImageFormat, RgbImage};
+
+/// Layout of the raw camera buffer handed to the engine.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PixelFormat {
+    Rgb,
+    Bgr,
+    Rgba,
+    Bgra,
+    Gray,
+    /// Packed 4:2:2, [Y0, U, Y1, V] per pixel pair
+    Yuyv,
+    /// Planar 4:2:0, full Y plane followed by interleaved UV at half resolution
+    Nv12,
+    Mjpeg,
+}
+
+impl PixelFormat {
+    /// Bytes per pixel on a row (luma row for NV12). `None` for compressed formats.
+    fn row_bytes_per_pixel(self) -> Option<usize> {
+        match self {
+            PixelFormat::Rgb | PixelFormat::Bgr => Some(3),
+            PixelFormat::Rgba | PixelFormat::Bgra => Some(4),
+            PixelFormat::Gray | PixelFormat::Nv12 => Some(1),
+            PixelFormat::Yuyv => Some(2),
+            PixelFormat::Mjpeg => None,
+        }
+    }
+}
+
+/// YUV -> RGB coefficients. Webcams at SD resolutions usually use BT.601, HD ones BT.709.
+/// Both are treated as full range (0..255), matching what UVC devices deliver in practice.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ColorMatrix {
+    #[default]
+    Bt601,
+    Bt709,
+}
+
+impl ColorMatrix {
+    /// Best guess when the driver does not report colorimetry.
+    pub fn for_height(height: u32) -> Self {
+        if height >= 720 { ColorMatrix::Bt709 } else { ColorMatrix::Bt601 }
+    }
+
+    // (Kr, Kb) luma weights
+    fn weights(self) -> (f32, f32) {
+        match self {
+            ColorMatrix::Bt601 => (0.299, 0.114),
+            ColorMatrix::Bt709 => (0.2126, 0.0722),
+        }
+    }
+}
+
+/// One captured frame. `stride` is the byte length of a row including padding
+/// (defaults to the tightly packed width).
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub data: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub format: PixelFormat,
+    pub stride: Option<usize>,
+    pub matrix: ColorMatrix,
+}
+
+impl Frame {
+    pub fn new(data: Vec<u8>, width: u32, height: u32, format: PixelFormat) -> Self {
+        Self { data, width, height, format, stride: None, matrix: ColorMatrix::default() }
+    }
+
+    fn stride(&self, bytes_per_pixel: usize) -> usize {
+        self.stride.unwrap_or(self.width as usize * bytes_per_pixel)
+    }
+
+    /// Converts any supported format into tightly packed RGB8.
+    pub fn to_rgb(&self) -> Result<RgbImage, String> {
+        let (w, h) = (self.width as usize, self.height as usize);
+        if w == 0 || h == 0 { return Err("Empty frame".to_string()); }
+
+        let Some(bpp) = self.format.row_bytes_per_pixel() else {
+            // MJPEG: the decoder takes care of everything, stride does not apply
+            let img = image::load_from_memory_with_format(&self.data, ImageFormat::Jpeg)
+                .map_err(|e| format!("MJPEG decode failed: {}", e))?;
+            return Ok(img.to_rgb8());
+        };
+
+        let stride = self.stride(bpp);
+        if stride < w * bpp {
+            return Err(format!("Stride {} is shorter than a {}px {:?} row", stride, w, self.format));
+        }
+        // NV12 ends on a chroma row: one UV pair per two pixels, so odd widths round up
+        let (plane_rows, last_row) = if self.format == PixelFormat::Nv12 {
+            (h + h.div_ceil(2), w.div_ceil(2) * 2)
+        } else {
+            (h, w * bpp)
+        };
+        let expected = stride * (plane_rows - 1) + last_row;
+        if self.data.len() < expected {
+            return Err(format!("Buffer Mismatch! {:?} {}x{} (stride {}) needs {} bytes, got {} bytes",
+                self.format, w, h, stride, expected, self.data.len()));
+        }
+
+        let mut rgb = Vec::with_capacity(w * h * 3);
+        let yuv = YuvConverter::new(self.matrix);
+
+        match self.format {
+            PixelFormat::Rgb | PixelFormat::Bgr | PixelFormat::Rgba | PixelFormat::Bgra => {
+                let swap = matches!(self.format, PixelFormat::Bgr | PixelFormat::Bgra);
+                for row in self.data.chunks(stride).take(h) {
+                    for px in row[..w * bpp].chunks_exact(bpp) {
+                        if swap { rgb.extend_from_slice(&[px[2], px[1], px[0]]); }
+                        else { rgb.extend_from_slice(&px[..3]); }
+                    }
+                }
+            }
+            PixelFormat::Gray => {
+                for row in self.data.chunks(stride).take(h) {
+                    for &g in &row[..w] { rgb.extend_from_slice(&[g, g, g]); }
+                }
+            }
+            PixelFormat::Yuyv => {
+                for row in self.data.chunks(stride).take(h) {
+                    for (i, chunk) in row[..w * 2].chunks(4).enumerate() {
+                        let (u, v) = (chunk.get(1).copied().unwrap_or(128), chunk.get(3).copied().unwrap_or(128));
+                        rgb.extend_from_slice(&yuv.convert(chunk[0], u, v));
+                        // Odd widths end on a half pair
+                        if i * 2 + 1 < w { rgb.extend_from_slice(&yuv.convert(chunk[2], u, v)); }
+                    }
+                }
+            }
+            PixelFormat::Nv12 => {
+                let uv_plane = &self.data[stride * h..];
+                for y in 0..h {
+                    let luma = &self.data[y * stride..y * stride + w];
+                    let chroma = &uv_plane[(y / 2) * stride..];
+                    for (x, &l) in luma.iter().enumerate() {
+                        let c = (x / 2) * 2;
+                        let (u, v) = (chroma.get(c).copied().unwrap_or(128), chroma.get(c + 1).copied().unwrap_or(128));
+                        rgb.extend_from_slice(&yuv.convert(l, u, v));
+                    }
+                }
+            }
+            PixelFormat::Mjpeg => unreachable!("handled above"),
+        }
+
+        RgbImage::from_raw(self.width, self.height, rgb).ok_or_else(|| "Failed to create RGB buffer".to_string())
+    }
+}
+
+struct YuvConverter {
+    rv: f32,
+    gu: f32,
+    gv: f32,
+    bu: f32,
+}
+
+impl YuvConverter {
+    fn new(matrix: ColorMatrix) -> Self {
+        let (kr, kb) = matrix.weights();
+        let kg = 1.0 - kr - kb;
+        Self {
+            rv: 2.0 * (1.0 - kr),
+            gu: 2.0 * kb * (1.0 - kb) / kg,
+            gv: 2.0 * kr * (1.0 - kr) / kg,
+            bu: 2.0 * (1.0 - kb),
+        }
+    }
+
+    fn convert(&self, y: u8, u: u8, v: u8) -> [u8; 3] {
+        let y = y as f32;
+        let u = u as f32 - 128.0;
+        let v = v as f32 - 128.0;
+        let clamp = |c: f32| c.round().clamp(0.0, 255.0) as u8;
+        [
+            clamp(y + self.rv * v),
+            clamp(y - self.gu * u - self.gv * v),
+            clamp(y + self.bu * u),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RGB (200, 100, 50) encoded as full-range YUV
+    const PATCH_601: (u8, u8, u8) = (124, 86, 182);
+    const PATCH_709: (u8, u8, u8) = (118, 92, 180);
+
+    fn assert_close(actual: &[u8], expected: [u8; 3], tolerance: u8) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(a.abs_diff(e) <= tolerance, "got {:?}, expected {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn bgr_and_bgra_swap_channels() {
+        let bgr = Frame::new(vec![50, 100, 200], 1, 1, PixelFormat::Bgr).to_rgb().unwrap();
+        assert_eq!(bgr.as_raw(), &vec![200, 100, 50]);
+
+        let bgra = Frame::new(vec![50, 100, 200, 255], 1, 1, PixelFormat::Bgra).to_rgb().unwrap();
+        assert_eq!(bgra.as_raw(), &vec![200, 100, 50]);
+    }
+
+    #[test]
+    fn padded_rows_are_skipped() {
+        // 2x2 RGBA with 4 bytes of padding per row
+        let mut frame = Frame::new(vec![
+            1, 2, 3, 0, 4, 5, 6, 0, 9, 9, 9, 9,
+            7, 8, 9, 0, 10, 11, 12, 0, 9, 9, 9, 9,
+        ], 2, 2, PixelFormat::Rgba);
+        frame.stride = Some(12);
+        assert_eq!(frame.to_rgb().unwrap().as_raw(), &vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+    }
+
+    #[test]
+    fn gray_expands_to_rgb() {
+        let img = Frame::new(vec![0, 128], 2, 1, PixelFormat::Gray).to_rgb().unwrap();
+        assert_eq!(img.as_raw(), &vec![0, 0, 0, 128, 128, 128]);
+    }
+
+    #[test]
+    fn yuyv_patch_matches_bt601_and_bt709() {
+        let (y, u, v) = PATCH_601;
+        let img = Frame::new(vec![y, u, y, v], 2, 1, PixelFormat::Yuyv).to_rgb().unwrap();
+        assert_close(&img.as_raw()[..3], [200, 100, 50], 1);
+        assert_close(&img.as_raw()[3..], [200, 100, 50], 1);
+
+        let (y, u, v) = PATCH_709;
+        let mut frame = Frame::new(vec![y, u, y, v], 2, 1, PixelFormat::Yuyv);
+        frame.matrix = ColorMatrix::Bt709;
+        assert_close(frame.to_rgb().unwrap().as_raw(), [200, 100, 50], 1);
+    }
+
+    #[test]
+    fn wrong_matrix_shifts_colour() {
+        let (y, u, v) = PATCH_601;
+        let mut frame = Frame::new(vec![y, u, y, v], 2, 1, PixelFormat::Yuyv);
+        frame.matrix = ColorMatrix::Bt709;
+        let red = frame.to_rgb().unwrap().as_raw()[0];
+        assert!(red > 205, "BT.709 decode of BT.601 data should overshoot red, got {}", red);
+    }
+
+    #[test]
+    fn yuyv_neutral_grey() {
+        let img = Frame::new(vec![128, 128, 128, 128], 2, 1, PixelFormat::Yuyv).to_rgb().unwrap();
+        assert_eq!(img.as_raw(), &vec![128; 6]);
+    }
+
+    #[test]
+    fn nv12_with_stride() {
+        let (y, u, v) = PATCH_601;
+        // 2x2 luma + one UV pair, rows padded to 4 bytes
+        let mut frame = Frame::new(vec![
+            y, y, 0, 0,
+            y, y, 0, 0,
+            u, v, 0, 0,
+        ], 2, 2, PixelFormat::Nv12);
+        frame.stride = Some(4);
+        let img = frame.to_rgb().unwrap();
+        for px in img.as_raw().chunks_exact(3) {
+            assert_close(px, [200, 100, 50], 1);
+        }
+    }
+
+    #[test]
+    fn nv12_odd_width_rounds_chroma_up() {
+        let (y, u, v) = PATCH_601;
+        // 3x2 luma + one chroma row of two UV pairs, the second covering only the last column
+        let img = Frame::new(vec![
+            y, y, y,
+            y, y, y,
+            u, v, u, v,
+        ], 3, 2, PixelFormat::Nv12).to_rgb().unwrap();
+        assert_eq!(img.dimensions(), (3, 2));
+        for px in img.as_raw().chunks_exact(3) {
+            assert_close(px, [200, 100, 50], 1);
+        }
+
+        // Missing the trailing V byte is a short buffer, not a panic
+        let err = Frame::new(vec![y; 9], 3, 2, PixelFormat::Nv12).to_rgb().unwrap_err();
+        assert!(err.contains("Buffer Mismatch"));
+    }
+
+    #[test]
+    fn mjpeg_decodes_solid_patch() {
+        let patch = RgbImage::from_pixel(16, 16, image::Rgb([200, 100, 50]));
+        let mut jpeg = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 95)
+            .encode_image(&patch)
+            .unwrap();
+
+        let img = Frame::new(jpeg, 16, 16, PixelFormat::Mjpeg).to_rgb().unwrap();
+        assert_eq!(img.dimensions(), (16, 16));
+        assert_close(&img.as_raw()[..3], [200, 100, 50], 6);
+    }
+
+    #[test]
+    fn short_buffer_is_rejected() {
+        let err = Frame::new(vec![0; 5], 2, 1, PixelFormat::Rgb).to_rgb().unwrap_err();
+        assert!(err.contains("Buffer Mismatch"));
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use image::{imageops, imageops::FilterType, RgbImage};
+use serde::Serialize;
+use super::Landmark;
+use super::keypoints::{visible, LEFT_EAR, LEFT_EYE, LEFT_SHOULDER, NOSE, RIGHT_EAR, RIGHT_EYE, RIGHT_SHOULDER};
+
+// Image statistics are taken on a small grey thumbnail; plenty for exposure/focus and cheap at 15 FPS
+const THUMB_SIZE: (u32, u32) = (160, 120);
+// The points every posture metric depends on
+const KEY_POINTS: [usize; 7] = [NOSE, LEFT_EYE, RIGHT_EYE, LEFT_EAR, RIGHT_EAR, LEFT_SHOULDER, RIGHT_SHOULDER];
+// A shoulder this close to the frame edge is probably clipped
+const EDGE_MARGIN: f32 = 0.02;
+
+/// Thresholds, read from the settings table:
+///   quality_min_luminance (0..255), quality_min_sharpness (Laplacian variance),
+///   quality_min_key_visible (0..1 fraction of head/shoulder points)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QualityConfig {
+    pub min_luminance: f32,
+    pub min_sharpness: f32,
+    pub min_key_visible: f32,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        Self { min_luminance: 40.0, min_sharpness: 25.0, min_key_visible: 0.6 }
+    }
+}
+
+impl QualityConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let d = Self::default();
+        let get = |key: &str| settings.get(key).and_then(|v| v.parse::<f32>().ok());
+        Self {
+            min_luminance: get("quality_min_luminance").unwrap_or(d.min_luminance),
+            min_sharpness: get("quality_min_sharpness").unwrap_or(d.min_sharpness),
+            min_key_visible: get("quality_min_key_visible").unwrap_or(d.min_key_visible),
+        }
+    }
+}
+
+/// Why a frame is not fit for scoring, in the order they are checked.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QualityIssue {
+    TooDark,
+    NoPerson,
+    ShouldersOutOfFrame,
+    Occluded,
+    TooBlurry,
+    /// Set by the tracker rather than `assess`: someone else is in view.
+    MultiplePeople,
+}
+
+impl QualityIssue {
+    pub fn message(self) -> &'static str {
+        match self {
+            QualityIssue::TooDark => "Too dark - turn on a light",
+            QualityIssue::NoPerson => "Nobody in view",
+            QualityIssue::ShouldersOutOfFrame => "Move back so both shoulders are in view",
+            QualityIssue::Occluded => "Face or shoulders are hidden",
+            QualityIssue::TooBlurry => "Image is blurry - check the lens or hold still",
+            QualityIssue::MultiplePeople => "Someone else is in view - scoring paused",
+        }
+    }
+}
+
+/// Per-frame measurements plus the verdict. `issue` is `None` when the frame can be scored.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FrameQuality {
+    pub luminance: f32,
+    pub sharpness: f32,
+    pub key_visible: f32,
+    pub shoulders_in_frame: bool,
+    pub issue: Option<QualityIssue>,
+    pub message: Option<String>,
+}
+
+impl FrameQuality {
+    pub fn is_usable(&self) -> bool {
+        self.issue.is_none()
+    }
+}
+
+/// Image measurements, independent of the pose: mean luma and Laplacian variance.
+pub fn image_stats(img: &RgbImage) -> (f32, f32) {
+    let gray = imageops::grayscale(img);
+    let thumb = imageops::resize(&gray, THUMB_SIZE.0, THUMB_SIZE.1, FilterType::Triangle);
+    let (w, h) = thumb.dimensions();
+    let px = |x: u32, y: u32| thumb.get_pixel(x, y).0[0] as f32;
+
+    let luminance = thumb.pixels().map(|p| p.0[0] as f32).sum::<f32>() / (w * h) as f32;
+
+    // 4-neighbour Laplacian; sharp edges give a wide response, blur flattens it
+    let mut responses = Vec::with_capacity(((w - 2) * (h - 2)) as usize);
+    for y in 1..h - 1 {
+        for x in 1..w - 1 {
+            responses.push(px(x - 1, y) + px(x + 1, y) + px(x, y - 1) + px(x, y + 1) - 4.0 * px(x, y));
+        }
+    }
+    let mean = responses.iter().sum::<f32>() / responses.len() as f32;
+    let sharpness = responses.iter().map(|r| (r - mean).powi(2)).sum::<f32>() / responses.len() as f32;
+
+    (luminance, sharpness)
+}
+
+/// Combines image stats with the selected user's landmarks (empty if nobody was found).
+pub fn assess(img: &RgbImage, landmarks: &[Landmark], config: &QualityConfig) -> FrameQuality {
+    let (luminance, sharpness) = image_stats(img);
+
+    let key_visible = KEY_POINTS.iter().filter(|&&i| visible(landmarks, i).is_some()).count() as f32 / KEY_POINTS.len() as f32;
+    let in_frame = |lm: &Landmark| {
+        lm.x >= EDGE_MARGIN && lm.x <= 1.0 - EDGE_MARGIN && lm.y >= EDGE_MARGIN && lm.y <= 1.0 - EDGE_MARGIN
+    };
+    let shoulders_in_frame = [LEFT_SHOULDER, RIGHT_SHOULDER].iter()
+        .all(|&i| visible(landmarks, i).is_some_and(in_frame));
+
+    // A dark room also makes everything else fail, so it goes first and gets the blame
+    let issue = if luminance < config.min_luminance {
+        Some(QualityIssue::TooDark)
+    } else if landmarks.is_empty() {
+        Some(QualityIssue::NoPerson)
+    } else if !shoulders_in_frame {
+        Some(QualityIssue::ShouldersOutOfFrame)
+    } else if key_visible < config.min_key_visible {
+        Some(QualityIssue::Occluded)
+    } else if sharpness < config.min_sharpness {
+        Some(QualityIssue::TooBlurry)
+    } else {
+        None
+    };
+
+    FrameQuality {
+        luminance,
+        sharpness,
+        key_visible,
+        shoulders_in_frame,
+        issue,
+        message: issue.map(|i| i.message().to_string()),
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// Where setups from before the manifest kept their single model, next to `models/`.
+pub const LEGACY_MODEL_FILE: &str = "pose_model.onnx";
+
+/// One model in `resources/models/manifest.json`:
+///   { "name": "blazepose-lite", "version": "1.0.0", "file": "blazepose_lite.onnx",
+///     "input_layout": "nhwc", "output_layout": "blazepose", "sha256": "…" }
+/// `output_layout` takes the same values as the `pose_layout` model metadata and wins over it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelEntry {
+    pub name: String,
+    pub version: String,
+    pub file: String,
+    #[serde(default)]
+    pub input_layout: Option<String>,
+    #[serde(default)]
+    pub output_layout: Option<String>,
+    pub sha256: String,
+}
+
+impl ModelEntry {
+    /// What gets stored with each session, e.g. "blazepose-lite@1.0.0".
+    pub fn version_tag(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    models: Vec<ModelEntry>,
+}
+
+/// The models shipped in a directory, as listed by its manifest.
+#[derive(Debug, Clone)]
+pub struct ModelRegistry {
+    dir: PathBuf,
+    models: Vec<ModelEntry>,
+    // Only set for the pre-manifest fallback, which has no checksum to check against
+    unverified: bool,
+}
+
+impl ModelRegistry {
+    pub fn load(dir: PathBuf) -> Result<Self, String> {
+        let manifest_path = dir.join(MANIFEST_FILE);
+        let raw = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Cannot read model manifest {:?}: {}", manifest_path, e))?;
+        let manifest: Manifest = serde_json::from_str(&raw)
+            .map_err(|e| format!("Invalid model manifest {:?}: {}", manifest_path, e))?;
+
+        // Files must sit in `dir` itself; "../" or an absolute path would load anything on disk
+        if let Some(entry) = manifest.models.iter().find(|m| !is_plain_file_name(&m.file)) {
+            return Err(format!("Model {} in {:?} must name a file in the models folder, got {:?}",
+                entry.version_tag(), manifest_path, entry.file));
+        }
+        Ok(Self { dir, models: manifest.models, unverified: false })
+    }
+
+    /// Wraps a pre-manifest `pose_model.onnx` in `dir`. There is no published checksum for it,
+    /// so it loads unverified (empty `sha256`) until it is moved into the manifest.
+    pub fn legacy(dir: PathBuf) -> Self {
+        let entry = ModelEntry {
+            name: "pose_model".to_string(),
+            version: "legacy".to_string(),
+            file: LEGACY_MODEL_FILE.to_string(),
+            input_layout: None,
+            output_layout: None,
+            sha256: String::new(),
+        };
+        Self { dir, models: vec![entry], unverified: true }
+    }
+
+    pub fn models(&self) -> &[ModelEntry] {
+        &self.models
+    }
+
+    /// Looks a model up by name; `None` picks the first one in the manifest.
+    pub fn find(&self, name: Option<&str>) -> Result<&ModelEntry, String> {
+        match name {
+            Some(name) => self.models.iter().find(|m| m.name == name)
+                .ok_or_else(|| format!("No model named '{}' in the manifest", name)),
+            None => self.models.first()
+                .ok_or_else(|| format!("Model manifest in {:?} lists no models", self.dir)),
+        }
+    }
+
+    /// Path to the model file, only once its SHA-256 matches the manifest.
+    /// The legacy fallback only checks that the file is there.
+    pub fn verified_path(&self, entry: &ModelEntry) -> Result<PathBuf, String> {
+        let path = self.dir.join(&entry.file);
+        if self.unverified {
+            fs::metadata(&path).map_err(|e| format!("Model missing at {:?}: {}", path, e))?;
+            return Ok(path);
+        }
+        let actual = sha256_file(&path)?;
+        if !actual.eq_ignore_ascii_case(entry.sha256.trim()) {
+            return Err(format!("Checksum mismatch for {} ({:?}): expected {}, got {}",
+                entry.version_tag(), path, entry.sha256, actual));
+        }
+        Ok(path)
+    }
+}
+
+fn is_plain_file_name(file: &str) -> bool {
+    Path::new(file).file_name().and_then(|name| name.to_str()) == Some(file)
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Model missing at {:?}: {}", path, e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 { break; }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SHA-256 of b"model"
+    const MODEL_SHA: &str = "9372c470eeadd5ecd9c3c74c2b3cb633f8e2f2fad799250a0f70d652b6b825e4";
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("posture-registry-{}", uuid::Uuid::new_v4()));
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn with_manifest(models: &str) -> Self {
+            let dir = Self::new();
+            fs::write(dir.0.join(MANIFEST_FILE), format!(r#"{{ "models": {} }}"#, models)).unwrap();
+            dir
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn entry_json(name: &str, file: &str, sha256: &str) -> String {
+        format!(r#"{{ "name": "{}", "version": "1.0.0", "file": "{}", "sha256": "{}" }}"#, name, file, sha256)
+    }
+
+    #[test]
+    fn matching_checksum_gives_the_path() {
+        let dir = TempDir::with_manifest(&format!("[{}]", entry_json("lite", "lite.onnx", &MODEL_SHA.to_uppercase())));
+        fs::write(dir.0.join("lite.onnx"), b"model").unwrap();
+
+        let registry = ModelRegistry::load(dir.0.clone()).unwrap();
+        let entry = registry.find(None).unwrap();
+        assert_eq!(registry.verified_path(entry).unwrap(), dir.0.join("lite.onnx"));
+    }
+
+    #[test]
+    fn checksum_mismatch_is_rejected() {
+        let dir = TempDir::with_manifest(&format!("[{}]", entry_json("lite", "lite.onnx", MODEL_SHA)));
+        fs::write(dir.0.join("lite.onnx"), b"tampered").unwrap();
+
+        let registry = ModelRegistry::load(dir.0.clone()).unwrap();
+        let err = registry.verified_path(registry.find(Some("lite")).unwrap()).unwrap_err();
+        assert!(err.contains("Checksum mismatch"), "{}", err);
+    }
+
+    #[test]
+    fn missing_file_is_reported() {
+        let dir = TempDir::with_manifest(&format!("[{}]", entry_json("lite", "lite.onnx", MODEL_SHA)));
+        let registry = ModelRegistry::load(dir.0.clone()).unwrap();
+        let err = registry.verified_path(registry.find(None).unwrap()).unwrap_err();
+        assert!(err.contains("Model missing"), "{}", err);
+    }
+
+    #[test]
+    fn empty_manifest_has_no_default() {
+        let dir = TempDir::with_manifest("[]");
+        let registry = ModelRegistry::load(dir.0.clone()).unwrap();
+        assert!(registry.models().is_empty());
+        assert!(registry.find(None).unwrap_err().contains("lists no models"));
+    }
+
+    #[test]
+    fn unknown_name_is_an_error() {
+        let dir = TempDir::with_manifest(&format!("[{}]", entry_json("lite", "lite.onnx", MODEL_SHA)));
+        let registry = ModelRegistry::load(dir.0.clone()).unwrap();
+        assert_eq!(registry.find(Some("full")).unwrap_err(), "No model named 'full' in the manifest");
+    }
+
+    #[test]
+    fn files_outside_the_folder_are_rejected() {
+        for file in ["../secret.onnx", "/etc/model.onnx", "nested/model.onnx", "..", ""] {
+            let dir = TempDir::with_manifest(&format!("[{}]", entry_json("lite", file, MODEL_SHA)));
+            let err = ModelRegistry::load(dir.0.clone()).unwrap_err();
+            assert!(err.contains("must name a file"), "{:?}: {}", file, err);
+        }
+    }
+
+    #[test]
+    fn legacy_model_loads_unverified() {
+        let dir = TempDir::new();
+        let registry = ModelRegistry::legacy(dir.0.clone());
+        let entry = registry.find(None).unwrap();
+        assert_eq!(entry.version_tag(), "pose_model@legacy");
+        assert!(entry.sha256.is_empty());
+        assert!(registry.verified_path(entry).unwrap_err().contains("Model missing"));
+
+        fs::write(dir.0.join(LEGACY_MODEL_FILE), b"model").unwrap();
+        assert_eq!(registry.verified_path(entry).unwrap(), dir.0.join(LEGACY_MODEL_FILE));
+
+        // A manifest entry with no checksum is still checked, and fails
+        let dir = TempDir::with_manifest(&format!("[{}]", entry_json("lite", "lite.onnx", "")));
+        fs::write(dir.0.join("lite.onnx"), b"model").unwrap();
+        let registry = ModelRegistry::load(dir.0.clone()).unwrap();
+        assert!(registry.verified_path(registry.find(None).unwrap()).unwrap_err().contains("Checksum mismatch"));
+    }
+}
@@ -0,0 +1,167 @@
+use image::{imageops, RgbImage};
+use super::Landmark;
+use super::keypoints::{visible, LEFT_EAR, LEFT_SHOULDER, MIN_VISIBILITY, NOSE, RIGHT_EAR, RIGHT_SHOULDER};
+
+// Grow the skeleton box so the next frame still fits if the user shifts a little
+const ROI_MARGIN: f32 = 0.35;
+// Never zoom in further than this fraction of the frame, keeps upscaling artefacts in check
+const MIN_ROI_SIZE: f32 = 0.25;
+// Head and shoulders are what we score; without them the crop is not worth trusting
+const ANCHOR_POINTS: [usize; 5] = [NOSE, LEFT_EAR, RIGHT_EAR, LEFT_SHOULDER, RIGHT_SHOULDER];
+const MIN_ANCHORS: usize = 3;
+
+/// Region of interest in normalised source-frame coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Roi {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Roi {
+    pub fn crop(&self, img: &RgbImage) -> RgbImage {
+        let (w, h) = (img.width() as f32, img.height() as f32);
+        let x = (self.x * w).floor() as u32;
+        let y = (self.y * h).floor() as u32;
+        let cw = ((self.width * w).round() as u32).clamp(1, img.width() - x);
+        let ch = ((self.height * h).round() as u32).clamp(1, img.height() - y);
+        imageops::crop_imm(img, x, y, cw, ch).to_image()
+    }
+
+    /// Crop-normalised landmark -> frame-normalised landmark.
+    pub fn unproject(&self, lm: &mut Landmark) {
+        lm.x = self.x + lm.x * self.width;
+        lm.y = self.y + lm.y * self.height;
+        lm.z *= self.width;
+    }
+}
+
+/// Detector/tracker split: once we have a skeleton, crop around it for the next frame.
+/// Holds no frame data, only the box derived from the last landmarks.
+#[derive(Debug, Default)]
+pub struct RoiTracker {
+    roi: Option<Roi>,
+}
+
+impl RoiTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Where to run the next inference. `None` means the full frame.
+    pub fn roi(&self) -> Option<Roi> {
+        self.roi
+    }
+
+    pub fn is_tracked(landmarks: &[Landmark]) -> bool {
+        ANCHOR_POINTS.iter()
+            .filter(|&&i| visible(landmarks, i).is_some())
+            .count() >= MIN_ANCHORS
+    }
+
+    pub fn update(&mut self, landmarks: &[Landmark]) {
+        if !Self::is_tracked(landmarks) {
+            self.roi = None;
+            return;
+        }
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+        for lm in landmarks.iter().filter(|lm| lm.visibility >= MIN_VISIBILITY) {
+            min_x = min_x.min(lm.x);
+            min_y = min_y.min(lm.y);
+            max_x = max_x.max(lm.x);
+            max_y = max_y.max(lm.y);
+        }
+
+        let grow = |min: f32, max: f32| -> (f32, f32) {
+            let size = ((max - min) * (1.0 + 2.0 * ROI_MARGIN)).clamp(MIN_ROI_SIZE, 1.0);
+            let start = ((min + max) / 2.0 - size / 2.0).clamp(0.0, 1.0 - size);
+            (start, size)
+        };
+        let (x, width) = grow(min_x, max_x);
+        let (y, height) = grow(min_y, max_y);
+
+        self.roi = Some(Roi { x, y, width, height });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::keypoints::LANDMARK_COUNT;
+
+    // Head and shoulders at the given (x, y) positions, everything else invisible
+    fn upper_body(points: [(f32, f32); 5]) -> Vec<Landmark> {
+        let mut landmarks = vec![Landmark { x: 0.0, y: 0.0, z: 0.0, visibility: 0.0 }; LANDMARK_COUNT];
+        for (&i, (x, y)) in ANCHOR_POINTS.iter().zip(points) {
+            landmarks[i] = Landmark { x, y, z: 0.0, visibility: 0.9 };
+        }
+        landmarks
+    }
+
+    // Skeleton spanning [x0, x1] x [y0, y1]
+    fn spanning(x0: f32, x1: f32, y0: f32, y1: f32) -> Vec<Landmark> {
+        let mid = (x0 + x1) / 2.0;
+        upper_body([(mid, y0), (x0, y0), (x1, y0), (x0, y1), (x1, y1)])
+    }
+
+    fn tracked(landmarks: &[Landmark]) -> Roi {
+        let mut tracker = RoiTracker::new();
+        tracker.update(landmarks);
+        tracker.roi().expect("should be tracking")
+    }
+
+    fn assert_roi(roi: Roi, expected: [f32; 4]) {
+        let actual = [roi.x, roi.y, roi.width, roi.height];
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "got {:?}, expected {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn box_grows_by_the_margin_on_each_side() {
+        // 0.2 x 0.3 skeleton centred on (0.5, 0.45)
+        let roi = tracked(&spanning(0.4, 0.6, 0.3, 0.6));
+        let (w, h) = (0.2 * (1.0 + 2.0 * ROI_MARGIN), 0.3 * (1.0 + 2.0 * ROI_MARGIN));
+        assert_roi(roi, [0.5 - w / 2.0, 0.45 - h / 2.0, w, h]);
+    }
+
+    #[test]
+    fn small_skeletons_get_the_minimum_size() {
+        let roi = tracked(&spanning(0.49, 0.51, 0.49, 0.51));
+        let start = 0.5 - MIN_ROI_SIZE / 2.0;
+        assert_roi(roi, [start, start, MIN_ROI_SIZE, MIN_ROI_SIZE]);
+    }
+
+    #[test]
+    fn box_is_kept_inside_the_frame() {
+        // Top-left corner: pushed right and down instead of starting off-frame
+        let roi = tracked(&spanning(0.0, 0.2, 0.0, 0.2));
+        let size = 0.2 * (1.0 + 2.0 * ROI_MARGIN);
+        assert_roi(roi, [0.0, 0.0, size, size]);
+
+        // Bottom-right corner: pulled back so it ends on the frame edge
+        let roi = tracked(&spanning(0.8, 1.0, 0.8, 1.0));
+        assert_roi(roi, [1.0 - size, 1.0 - size, size, size]);
+
+        // Larger than the frame once grown: the whole frame, never more
+        assert_roi(tracked(&spanning(0.05, 0.95, 0.05, 0.95)), [0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn losing_the_user_falls_back_to_the_full_frame() {
+        let mut tracker = RoiTracker::new();
+        tracker.update(&spanning(0.4, 0.6, 0.3, 0.6));
+        assert!(tracker.roi().is_some());
+
+        // Only two head/shoulder points left: not enough to trust a crop
+        let mut lost = spanning(0.4, 0.6, 0.3, 0.6);
+        for &i in &ANCHOR_POINTS[2..] { lost[i].visibility = 0.1; }
+        tracker.update(&lost);
+        assert_eq!(tracker.roi(), None);
+
+        tracker.update(&[]);
+        assert_eq!(tracker.roi(), None);
+    }
+}
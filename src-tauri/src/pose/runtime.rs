@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+#[cfg(feature = "backend-ort")]
+use ort::session::builder::{GraphOptimizationLevel, SessionBuilder};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OptimizationLevel { Disable, Basic, Extended, Layout, All }
+
+impl OptimizationLevel {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "disable" => Some(OptimizationLevel::Disable),
+            "basic" => Some(OptimizationLevel::Basic),
+            "extended" => Some(OptimizationLevel::Extended),
+            "layout" => Some(OptimizationLevel::Layout),
+            "all" => Some(OptimizationLevel::All),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "backend-ort")]
+    fn to_ort(self) -> GraphOptimizationLevel {
+        match self {
+            OptimizationLevel::Disable => GraphOptimizationLevel::Disable,
+            OptimizationLevel::Basic => GraphOptimizationLevel::Level1,
+            OptimizationLevel::Extended => GraphOptimizationLevel::Level2,
+            OptimizationLevel::Layout => GraphOptimizationLevel::Level3,
+            OptimizationLevel::All => GraphOptimizationLevel::All,
+        }
+    }
+}
+
+/// ONNX Runtime session options, read from the settings table:
+///   ort_intra_threads, ort_inter_threads: thread counts (0 lets ORT decide)
+///   ort_optimization_level: "disable", "basic", "extended", "layout" (default) or "all"
+///   ort_memory_arena: "true" (default) / "false"
+///   ort_execution_mode: "sequential" (default) or "parallel"
+/// Defaults match what we shipped before these were configurable. Only the ORT backend uses them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RuntimeConfig {
+    pub intra_threads: usize,
+    pub inter_threads: usize,
+    pub optimization_level: OptimizationLevel,
+    pub memory_arena: bool,
+    /// Only pays off for graphs with parallel branches; `inter_threads` sizes its pool.
+    pub parallel_execution: bool,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            intra_threads: 1,
+            inter_threads: 1,
+            optimization_level: OptimizationLevel::Layout,
+            memory_arena: true,
+            parallel_execution: false,
+        }
+    }
+}
+
+impl RuntimeConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let d = Self::default();
+        let flag = |key: &str| settings.get(key).and_then(|v| v.parse::<bool>().ok());
+        Self {
+            intra_threads: settings.get("ort_intra_threads").and_then(|v| v.parse().ok()).unwrap_or(d.intra_threads),
+            inter_threads: settings.get("ort_inter_threads").and_then(|v| v.parse().ok()).unwrap_or(d.inter_threads),
+            optimization_level: settings.get("ort_optimization_level")
+                .and_then(|v| OptimizationLevel::parse(v))
+                .unwrap_or(d.optimization_level),
+            memory_arena: flag("ort_memory_arena").unwrap_or(d.memory_arena),
+            parallel_execution: settings.get("ort_execution_mode")
+                .map(|v| v == "parallel")
+                .unwrap_or(d.parallel_execution),
+        }
+    }
+
+    #[cfg(feature = "backend-ort")]
+    pub fn apply(&self, builder: SessionBuilder) -> Result<SessionBuilder, String> {
+        builder
+            .with_optimization_level(self.optimization_level.to_ort())
+            .map_err(|e| format!("Optimization error: {}", e))?
+            .with_intra_threads(self.intra_threads)
+            .map_err(|e| format!("Threads error: {}", e))?
+            .with_inter_threads(self.inter_threads)
+            .map_err(|e| format!("Threads error: {}", e))?
+            .with_parallel_execution(self.parallel_execution)
+            .map_err(|e| format!("Execution mode error: {}", e))?
+            .with_execution_providers([ort::ep::CPU::default().with_arena_allocator(self.memory_arena).build()])
+            .map_err(|e| format!("Execution provider error: {}", e))
+    }
+}
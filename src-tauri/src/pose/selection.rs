@@ -0,0 +1,114 @@
+use super::{DecodedPose, Landmark};
+use super::decoder::MIN_PERSON_SCORE;
+use super::keypoints::MIN_VISIBILITY;
+
+// A detection needs MIN_PERSON_SCORE and this many confident points to count as a person
+const MIN_VISIBLE_POINTS: usize = 5;
+// A candidate further than this (normalised) from where we expect the user gets no continuity credit
+const MAX_JUMP: f32 = 0.35;
+
+const WEIGHT_SIZE: f32 = 0.3;
+const WEIGHT_CENTRALITY: f32 = 0.2;
+const WEIGHT_CONTINUITY: f32 = 0.5;
+
+/// Where we expect the user to be: centre of the visible skeleton.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anchor {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Anchor {
+    pub fn of(landmarks: &[Landmark]) -> Option<Self> {
+        let visible: Vec<&Landmark> = landmarks.iter().filter(|lm| lm.visibility >= MIN_VISIBILITY).collect();
+        if visible.is_empty() { return None; }
+        let n = visible.len() as f32;
+        Some(Self {
+            x: visible.iter().map(|lm| lm.x).sum::<f32>() / n,
+            y: visible.iter().map(|lm| lm.y).sum::<f32>() / n,
+        })
+    }
+
+    fn distance(&self, other: &Anchor) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Selection {
+    /// Landmarks of the user we track, empty when nobody qualifies.
+    pub primary: Vec<Landmark>,
+    /// Metric world landmarks of the same person, if the model produces them.
+    pub world: Option<Vec<Landmark>>,
+    /// Everyone who looks like a real person, including the primary.
+    pub people: usize,
+}
+
+impl Selection {
+    pub fn multiple_people(&self) -> bool {
+        self.people > 1
+    }
+}
+
+/// Picks the user out of a multi-person result by size, centrality and continuity,
+/// so a colleague walking behind the desk does not take over the skeleton.
+#[derive(Debug, Default)]
+pub struct PrimarySelector {
+    last: Option<Anchor>,
+    // Long-term expectation (e.g. the calibrated position); used when `last` is lost
+    baseline: Option<Anchor>,
+}
+
+impl PrimarySelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_baseline(&mut self, baseline: Option<Anchor>) {
+        self.baseline = baseline;
+    }
+
+    pub fn select(&mut self, poses: Vec<DecodedPose>) -> Selection {
+        let people: Vec<(DecodedPose, Anchor, f32)> = poses.into_iter()
+            .filter(|p| p.score >= MIN_PERSON_SCORE)
+            .filter(|p| p.landmarks.iter().filter(|lm| lm.visibility >= MIN_VISIBILITY).count() >= MIN_VISIBLE_POINTS)
+            .filter_map(|p| {
+                let anchor = Anchor::of(&p.landmarks)?;
+                let size = extent(&p.landmarks);
+                Some((p, anchor, size))
+            })
+            .collect();
+
+        let count = people.len();
+        let largest = people.iter().map(|(_, _, size)| *size).fold(f32::EPSILON, f32::max);
+        let expected = self.last.or(self.baseline);
+
+        let best = people.into_iter().max_by(|a, b| {
+            let score = |(_, anchor, size): &(DecodedPose, Anchor, f32)| {
+                let centrality = 1.0 - (Anchor { x: 0.5, y: 0.5 }.distance(anchor) / 0.71).min(1.0);
+                let continuity = match expected {
+                    Some(e) => 1.0 - (e.distance(anchor) / MAX_JUMP).min(1.0),
+                    None => 0.5,
+                };
+                WEIGHT_SIZE * (size / largest) + WEIGHT_CENTRALITY * centrality + WEIGHT_CONTINUITY * continuity
+            };
+            score(a).total_cmp(&score(b))
+        });
+
+        self.last = best.as_ref().map(|(_, anchor, _)| *anchor);
+        let (primary, world) = best.map(|(pose, _, _)| (pose.landmarks, pose.world)).unwrap_or_default();
+        Selection { primary, world, people: count }
+    }
+}
+
+// Diagonal of the visible-point bounding box, a camera-distance proxy
+fn extent(landmarks: &[Landmark]) -> f32 {
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for lm in landmarks.iter().filter(|lm| lm.visibility >= MIN_VISIBILITY) {
+        min_x = min_x.min(lm.x);
+        min_y = min_y.min(lm.y);
+        max_x = max_x.max(lm.x);
+        max_y = max_y.max(lm.y);
+    }
+    ((max_x - min_x).powi(2) + (max_y - min_y).powi(2)).sqrt()
+}
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use super::Landmark;
+use super::keypoints::MIN_VISIBILITY;
+
+// Fallback frame period when two samples share a timestamp
+const DEFAULT_DT: f64 = 1.0 / 15.0;
+// Below this many trusted landmarks we treat the user as gone
+const MIN_TRUSTED_FOR_PRESENCE: usize = 4;
+// Held points start at this fraction of the trust threshold, so nothing downstream counts them as seen
+const HELD_VISIBILITY: f32 = 0.9;
+
+/// Tunables, read from the settings table:
+///   smoothing_min_cutoff (Hz), smoothing_beta, smoothing_min_visibility,
+///   smoothing_hold_sec, smoothing_reset_sec
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmoothingConfig {
+    /// Cutoff at rest. Lower = steadier but laggier.
+    pub min_cutoff: f64,
+    /// How fast the cutoff opens up with speed. Higher = less lag on quick moves.
+    pub beta: f64,
+    pub d_cutoff: f64,
+    /// Points below this visibility are not fed to the filter.
+    pub min_visibility: f32,
+    /// How long an untrusted point keeps its last good position while fading out.
+    pub hold_sec: f64,
+    /// Absence longer than this wipes filter state, so a returning user starts fresh.
+    pub reset_sec: f64,
+}
+
+impl Default for SmoothingConfig {
+    fn default() -> Self {
+        Self { min_cutoff: 1.0, beta: 5.0, d_cutoff: 1.0, min_visibility: 0.5, hold_sec: 0.5, reset_sec: 1.0 }
+    }
+}
+
+impl SmoothingConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let d = Self::default();
+        let get = |key: &str| settings.get(key).and_then(|v| v.parse::<f64>().ok());
+        Self {
+            min_cutoff: get("smoothing_min_cutoff").unwrap_or(d.min_cutoff),
+            beta: get("smoothing_beta").unwrap_or(d.beta),
+            d_cutoff: d.d_cutoff,
+            min_visibility: get("smoothing_min_visibility").map(|v| v as f32).unwrap_or(d.min_visibility),
+            hold_sec: get("smoothing_hold_sec").unwrap_or(d.hold_sec),
+            reset_sec: get("smoothing_reset_sec").unwrap_or(d.reset_sec),
+        }
+    }
+}
+
+/// One Euro filter (Casiez et al. 2012): an adaptive low-pass whose cutoff rises with speed.
+#[derive(Debug, Clone, Default)]
+pub struct OneEuroFilter {
+    prev: Option<(f64, f64, f64)>, // (t, x_hat, dx_hat)
+}
+
+fn alpha(cutoff: f64, dt: f64) -> f64 {
+    let tau = 1.0 / (2.0 * PI * cutoff);
+    1.0 / (1.0 + tau / dt)
+}
+
+impl OneEuroFilter {
+    pub fn filter(&mut self, x: f64, t: f64, config: &SmoothingConfig) -> f64 {
+        let Some((t_prev, x_prev, dx_prev)) = self.prev else {
+            self.prev = Some((t, x, 0.0));
+            return x;
+        };
+
+        let dt = if t > t_prev { t - t_prev } else { DEFAULT_DT };
+        let dx = (x - x_prev) / dt;
+        let a_d = alpha(config.d_cutoff, dt);
+        let dx_hat = a_d * dx + (1.0 - a_d) * dx_prev;
+
+        let cutoff = config.min_cutoff + config.beta * dx_hat.abs();
+        let a = alpha(cutoff, dt);
+        let x_hat = a * x + (1.0 - a) * x_prev;
+
+        self.prev = Some((t, x_hat, dx_hat));
+        x_hat
+    }
+
+    pub fn reset(&mut self) {
+        self.prev = None;
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct PointState {
+    filters: [OneEuroFilter; 3],
+    // Last trusted output and when it was seen
+    last: Option<(Landmark, f64)>,
+}
+
+/// Stateful per-landmark smoother for the tracking loop.
+pub struct LandmarkSmoother {
+    config: SmoothingConfig,
+    points: Vec<PointState>,
+    absent_since: Option<f64>,
+}
+
+impl LandmarkSmoother {
+    pub fn new(config: SmoothingConfig) -> Self {
+        Self { config, points: Vec::new(), absent_since: None }
+    }
+
+    /// `t` is a monotonic timestamp in seconds.
+    pub fn smooth(&mut self, landmarks: &[Landmark], t: f64) -> Vec<Landmark> {
+        let trusted = landmarks.iter().filter(|lm| lm.visibility >= self.config.min_visibility).count();
+
+        if trusted < MIN_TRUSTED_FOR_PRESENCE {
+            let since = *self.absent_since.get_or_insert(t);
+            if t - since >= self.config.reset_sec {
+                self.points.clear();
+            }
+        } else {
+            self.absent_since = None;
+        }
+
+        // No skeleton this frame (e.g. a one-frame miss): keep the filters for when it comes back
+        if landmarks.is_empty() {
+            return Vec::new();
+        }
+
+        if self.points.len() != landmarks.len() {
+            self.points = vec![PointState::default(); landmarks.len()];
+        }
+
+        let config = self.config;
+        let held_cap = config.min_visibility.min(MIN_VISIBILITY) * HELD_VISIBILITY;
+        landmarks.iter().zip(self.points.iter_mut()).map(|(lm, point)| {
+            if lm.visibility >= config.min_visibility {
+                let [fx, fy, fz] = &mut point.filters;
+                let smoothed = Landmark {
+                    x: fx.filter(lm.x as f64, t, &config) as f32,
+                    y: fy.filter(lm.y as f64, t, &config) as f32,
+                    z: fz.filter(lm.z as f64, t, &config) as f32,
+                    visibility: lm.visibility,
+                };
+                point.last = Some((smoothed.clone(), t));
+                return smoothed;
+            }
+
+            // Untrusted: hold the last good position, below the trust threshold, and fade it out
+            if let Some((last, seen)) = &point.last {
+                let elapsed = t - seen;
+                if elapsed < config.hold_sec {
+                    let fade = 1.0 - (elapsed / config.hold_sec) as f32;
+                    return Landmark { visibility: last.visibility.min(held_cap) * fade, ..last.clone() };
+                }
+            }
+
+            // Held too long: forget it so it re-converges from scratch when it comes back
+            point.filters.iter_mut().for_each(OneEuroFilter::reset);
+            point.last = None;
+            lm.clone()
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FPS: f64 = 15.0;
+
+    // Deterministic noise in [-amplitude, amplitude]
+    fn noise(seed: &mut u64, amplitude: f32) -> f32 {
+        *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((*seed >> 33) as f32 / (1u64 << 31) as f32 * 2.0 - 1.0) * amplitude
+    }
+
+    fn skeleton(x: f32, y: f32, visibility: f32) -> Vec<Landmark> {
+        vec![Landmark { x, y, z: 0.0, visibility }; 33]
+    }
+
+    fn variance(values: &[f32]) -> f32 {
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32
+    }
+
+    #[test]
+    fn steady_noisy_track_is_calmed() {
+        let mut smoother = LandmarkSmoother::new(SmoothingConfig::default());
+        let mut seed = 42;
+        let (mut raw, mut smoothed) = (Vec::new(), Vec::new());
+
+        for i in 0..150 {
+            let x = 0.5 + noise(&mut seed, 0.02);
+            let out = smoother.smooth(&skeleton(x, 0.4, 0.9), i as f64 / FPS);
+            if i >= 30 {
+                raw.push(x);
+                smoothed.push(out[0].x);
+            }
+        }
+
+        assert!(variance(&smoothed) < variance(&raw) * 0.5,
+            "smoothed variance {} vs raw {}", variance(&smoothed), variance(&raw));
+    }
+
+    #[test]
+    fn step_change_is_followed() {
+        let mut smoother = LandmarkSmoother::new(SmoothingConfig::default());
+        for i in 0..30 { smoother.smooth(&skeleton(0.3, 0.4, 0.9), i as f64 / FPS); }
+
+        let mut last = 0.0;
+        for i in 30..60 { last = smoother.smooth(&skeleton(0.6, 0.4, 0.9), i as f64 / FPS)[0].x; }
+
+        assert!((last - 0.6).abs() < 0.01, "did not converge after 2s, at {}", last);
+    }
+
+    #[test]
+    fn low_visibility_points_are_held_then_faded() {
+        let mut smoother = LandmarkSmoother::new(SmoothingConfig::default());
+        for i in 0..15 { smoother.smooth(&skeleton(0.5, 0.4, 0.9), i as f64 / FPS); }
+
+        // One point drops out and jumps somewhere implausible
+        let mut frame = skeleton(0.5, 0.4, 0.9);
+        frame[7] = Landmark { x: 0.9, y: 0.9, z: 0.0, visibility: 0.1 };
+
+        let held = smoother.smooth(&frame, 15.0 / FPS);
+        assert!((held[7].x - 0.5).abs() < 1e-3, "should hold last good x, got {}", held[7].x);
+        assert!(held[7].visibility < SmoothingConfig::default().min_visibility && held[7].visibility > 0.0,
+            "held point must not look trusted, got {}", held[7].visibility);
+
+        let later = smoother.smooth(&frame, 15.0 / FPS + 0.3);
+        assert!(later[7].visibility < held[7].visibility, "visibility should keep fading");
+
+        let expired = smoother.smooth(&frame, 15.0 / FPS + 0.6);
+        assert_eq!(expired[7].x, 0.9);
+        assert_eq!(expired[7].visibility, 0.1);
+    }
+
+    #[test]
+    fn one_empty_frame_keeps_filter_state() {
+        let mut smoother = LandmarkSmoother::new(SmoothingConfig::default());
+        for i in 0..15 { smoother.smooth(&skeleton(0.2, 0.4, 0.9), i as f64 / FPS); }
+
+        // No primary for one frame
+        assert!(smoother.smooth(&[], 15.0 / FPS).is_empty());
+
+        // Still filtered against the old position rather than restarting at the new sample
+        let back = smoother.smooth(&skeleton(0.8, 0.4, 0.9), 16.0 / FPS);
+        assert!(back[0].x < 0.8, "filter state was wiped, got {}", back[0].x);
+    }
+
+    #[test]
+    fn leaving_the_frame_resets_state() {
+        let mut smoother = LandmarkSmoother::new(SmoothingConfig::default());
+        for i in 0..15 { smoother.smooth(&skeleton(0.2, 0.4, 0.9), i as f64 / FPS); }
+
+        // Gone for 1.5s
+        for i in 15..38 { smoother.smooth(&skeleton(0.0, 0.0, 0.0), i as f64 / FPS); }
+
+        // Comes back on the other side; first sample is taken as-is, no drag from the old position
+        let back = smoother.smooth(&skeleton(0.8, 0.4, 0.9), 38.0 / FPS);
+        assert_eq!(back[0].x, 0.8);
+    }
+}
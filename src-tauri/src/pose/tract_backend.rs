@@ -0,0 +1,87 @@
+use std::path::Path;
+use image::RgbImage;
+use tract_onnx::prelude::*;
+use tract_onnx::tract_hir::infer::Factoid;
+use super::decoder::DecodedPose;
+use super::estimator::{negotiate_input, PoseEstimator, TensorPipeline};
+use super::input::{InputData, InputDType};
+use super::{ModelConfig, ModelEntry};
+
+/// Pure-Rust backend: no shared library to ship, works wherever the binary runs.
+/// Ignores the ORT session options; tract optimises the graph once at load.
+pub struct TractEstimator {
+    plan: TypedRunnableModel<TypedModel>,
+    pipeline: TensorPipeline,
+}
+
+impl TractEstimator {
+    pub fn load(path: &Path, entry: &ModelEntry, config: &ModelConfig) -> Result<Self, String> {
+        let model = tract_onnx::onnx()
+            .model_for_path(path)
+            .map_err(|e| format!("Load error: {}", e))?;
+
+        // Same negotiation as ORT; unknown dims come through as -1
+        let input_name = model.input_outlets().ok()
+            .and_then(|outlets| outlets.first())
+            .map(|outlet| model.node(outlet.node).name.clone())
+            .ok_or("Model has no inputs")?;
+        let fact = model.input_fact(0).map_err(|e| e.to_string())?;
+        let (input_dtype, datum_type) = match fact.datum_type.concretize() {
+            Some(DatumType::F32) => (InputDType::F32, DatumType::F32),
+            Some(DatumType::U8) => (InputDType::U8, DatumType::U8),
+            Some(DatumType::I32) => (InputDType::I32, DatumType::I32),
+            other => return Err(format!("Unsupported input element type: {:?}", other)),
+        };
+        let input_shape: Vec<i64> = fact.shape.dims()
+            .map(|d| d.concretize().and_then(|d| d.to_i64().ok()).unwrap_or(-1))
+            .collect();
+        let input = negotiate_input(&input_name, &input_shape, input_dtype, entry, config)?;
+
+        // tract needs a concrete input before it can type and optimise the graph
+        let typed = model
+            .with_input_fact(0, InferenceFact::dt_shape(datum_type, input.shape()))
+            .and_then(|m| m.into_optimized())
+            .map_err(|e| format!("Optimisation error: {}", e))?;
+        let output_shapes: Vec<Vec<i64>> = (0..typed.output_outlets().map(|o| o.len()).unwrap_or(1))
+            .map(|i| typed.output_fact(i).ok()
+                .and_then(|f| f.shape.as_concrete().map(|s| s.iter().map(|&d| d as i64).collect()))
+                .unwrap_or_default())
+            .collect();
+        // tract does not expose ONNX metadata_props, so only the manifest can override the shape
+        let pipeline = TensorPipeline::new(input, &output_shapes, entry, None)?;
+
+        let plan = typed.into_runnable().map_err(|e| format!("Plan error: {}", e))?;
+        Ok(Self { plan, pipeline })
+    }
+}
+
+impl PoseEstimator for TractEstimator {
+    fn describe(&self) -> String {
+        self.pipeline.describe("tract")
+    }
+
+    fn estimate(&mut self, img: &RgbImage) -> Result<Vec<DecodedPose>, String> {
+        let (letterbox, data) = self.pipeline.prepare(img);
+        let shape = self.pipeline.input.shape();
+        let tensor = match data {
+            InputData::F32(data) => Tensor::from_shape(&shape, &data),
+            InputData::U8(data) => Tensor::from_shape(&shape, &data),
+            InputData::I32(data) => Tensor::from_shape(&shape, &data),
+        }.map_err(|e| format!("Tensor creation failed: {}", e))?;
+
+        let outputs = self.plan.run(tvec!(tensor.into()))
+            .map_err(|e| format!("Inference failed: {}", e))?;
+        let output = outputs[0].cast_to::<f32>().map_err(|e| format!("Extraction failed: {}", e))?;
+        let data = output.as_slice::<f32>().map_err(|e| format!("Extraction failed: {}", e))?;
+        let world = self.pipeline.world_output
+            .map(|i| outputs[i].cast_to::<f32>())
+            .transpose()
+            .map_err(|e| format!("Extraction failed: {}", e))?;
+        let world = world.as_ref()
+            .map(|w| w.as_slice::<f32>())
+            .transpose()
+            .map_err(|e| format!("Extraction failed: {}", e))?;
+
+        self.pipeline.finish(data, world, &letterbox)
+    }
+}
@@ -0,0 +1,61 @@
+use serde::Serialize;
+use super::keypoints::*;
+use super::Landmark;
+
+/// Sagittal-plane posture measured from BlazePose world landmarks (metres, origin between
+/// the hips, y down, z away from the camera). A front camera sees forward head and slouch
+/// mostly as depth, which the image-space x/y deltas miss.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DepthMetrics {
+    /// How far the ears sit in front of the shoulders, towards the camera.
+    pub head_forward_cm: f32,
+    /// Tilt of the shoulder-to-ear line away from vertical, forward positive.
+    pub neck_flexion_deg: f32,
+    /// Tilt of the hip-to-shoulder line away from vertical, forward positive.
+    /// None when the hips are not visible, which is usual at a desk.
+    pub trunk_lean_deg: Option<f32>,
+    /// Left shoulder depth minus right: non-zero when the torso is turned.
+    pub shoulder_rotation_cm: f32,
+}
+
+impl DepthMetrics {
+    /// Needs both shoulders and at least one ear to be visible.
+    pub fn measure(world: &[Landmark]) -> Option<Self> {
+        let point = |i: usize| visible(world, i);
+        let (ls, rs) = (point(LEFT_SHOULDER)?, point(RIGHT_SHOULDER)?);
+        let ear = midpoint_of_visible(point(LEFT_EAR), point(RIGHT_EAR))?;
+        let shoulders = midpoint(ls, rs);
+
+        let hips = match (point(LEFT_HIP), point(RIGHT_HIP)) {
+            (Some(lh), Some(rh)) => Some(midpoint(lh, rh)),
+            _ => None,
+        };
+
+        Some(Self {
+            head_forward_cm: (shoulders.2 - ear.2) * 100.0,
+            neck_flexion_deg: sagittal_tilt(shoulders, ear),
+            trunk_lean_deg: hips.map(|hips| sagittal_tilt(hips, shoulders)),
+            shoulder_rotation_cm: (ls.z - rs.z) * 100.0,
+        })
+    }
+}
+
+fn midpoint(a: &Landmark, b: &Landmark) -> (f32, f32, f32) {
+    ((a.x + b.x) / 2.0, (a.y + b.y) / 2.0, (a.z + b.z) / 2.0)
+}
+
+// Profile views often hide one ear; the other is good enough
+fn midpoint_of_visible(a: Option<&Landmark>, b: Option<&Landmark>) -> Option<(f32, f32, f32)> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(midpoint(a, b)),
+        (Some(p), None) | (None, Some(p)) => Some((p.x, p.y, p.z)),
+        (None, None) => None,
+    }
+}
+
+// Angle of the lower->upper segment from vertical in the y/z plane; forward (towards the camera) is positive
+fn sagittal_tilt(lower: (f32, f32, f32), upper: (f32, f32, f32)) -> f32 {
+    let forward = lower.2 - upper.2;
+    let up = lower.1 - upper.1;
+    forward.atan2(up).to_degrees()
+}
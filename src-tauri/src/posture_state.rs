@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use crate::pose::Landmark;
+
+/// The one verdict alerts, session accounting and the overlay all key off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum PostureStatus {
+    Good,
+    Bad,
+    /// Someone is there but the frame cannot be scored (too dark, shoulders out of view...).
+    Unknown,
+    /// Nobody in view.
+    Away,
+}
+
+/// What one processed frame says, before debouncing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reading {
+    Scored(i64),
+    Unusable,
+    Absent,
+}
+
+/// Hysteresis and dwell times, read from the settings table:
+///   state_good_enter: a Bad user must score at least this to become Good (85)
+///   state_bad_enter: a Good user must score below this to become Bad (75)
+///   state_good_dwell_ms, state_bad_dwell_ms, state_unknown_dwell_ms, state_away_dwell_ms:
+///   how long a new status must hold before we switch to it (1500, 3000, 2000, 5000)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StateConfig {
+    pub good_enter: i64,
+    pub bad_enter: i64,
+    pub good_dwell: Duration,
+    pub bad_dwell: Duration,
+    pub unknown_dwell: Duration,
+    pub away_dwell: Duration,
+}
+
+impl Default for StateConfig {
+    fn default() -> Self {
+        Self {
+            good_enter: 85,
+            bad_enter: 75,
+            good_dwell: Duration::from_millis(1500),
+            // Slower to call posture bad than good, so the user is not nagged for shifting in the chair
+            bad_dwell: Duration::from_millis(3000),
+            unknown_dwell: Duration::from_millis(2000),
+            away_dwell: Duration::from_millis(5000),
+        }
+    }
+}
+
+impl StateConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let d = Self::default();
+        let get = |key: &str| settings.get(key).and_then(|v| v.parse::<i64>().ok());
+        let dwell = |key: &str, default: Duration| get(key).map(|ms| Duration::from_millis(ms.max(0) as u64)).unwrap_or(default);
+        Self {
+            good_enter: get("state_good_enter").unwrap_or(d.good_enter),
+            bad_enter: get("state_bad_enter").unwrap_or(d.bad_enter),
+            good_dwell: dwell("state_good_dwell_ms", d.good_dwell),
+            bad_dwell: dwell("state_bad_dwell_ms", d.bad_dwell),
+            unknown_dwell: dwell("state_unknown_dwell_ms", d.unknown_dwell),
+            away_dwell: dwell("state_away_dwell_ms", d.away_dwell),
+        }
+    }
+
+    fn dwell(&self, status: PostureStatus) -> Duration {
+        match status {
+            PostureStatus::Good => self.good_dwell,
+            PostureStatus::Bad => self.bad_dwell,
+            PostureStatus::Unknown => self.unknown_dwell,
+            PostureStatus::Away => self.away_dwell,
+        }
+    }
+}
+
+/// Debounces per-frame readings into a `PostureStatus`. Starts as Unknown.
+#[derive(Debug)]
+pub struct PostureStateMachine {
+    config: StateConfig,
+    status: PostureStatus,
+    // Latest score while Good or Bad
+    score: Option<i64>,
+    // The status we would switch to, and since when the readings have agreed
+    pending: Option<(PostureStatus, Instant)>,
+}
+
+impl PostureStateMachine {
+    pub fn new(config: StateConfig) -> Self {
+        Self { config, status: PostureStatus::Unknown, score: None, pending: None }
+    }
+
+    pub fn status(&self) -> PostureStatus {
+        self.status
+    }
+
+    pub fn score(&self) -> Option<i64> {
+        self.score
+    }
+
+    /// Returns the new status when this reading completes a transition.
+    pub fn update(&mut self, reading: Reading, now: Instant) -> Option<PostureStatus> {
+        let candidate = self.candidate(reading);
+        if let Reading::Scored(score) = reading {
+            if matches!(self.status, PostureStatus::Good | PostureStatus::Bad) {
+                self.score = Some(score);
+            }
+        }
+
+        if candidate == self.status {
+            self.pending = None;
+            return None;
+        }
+        let since = match self.pending {
+            Some((pending, since)) if pending == candidate => since,
+            _ => now,
+        };
+        if now.duration_since(since) < self.config.dwell(candidate) {
+            self.pending = Some((candidate, since));
+            return None;
+        }
+
+        self.status = candidate;
+        self.pending = None;
+        self.score = match reading {
+            Reading::Scored(score) => Some(score),
+            _ => None,
+        };
+        Some(candidate)
+    }
+
+    // Inside the hysteresis band a scored frame agrees with whichever of Good/Bad we are in;
+    // coming from Unknown or Away it goes by the middle of the band
+    fn candidate(&self, reading: Reading) -> PostureStatus {
+        let score = match reading {
+            Reading::Absent => return PostureStatus::Away,
+            Reading::Unusable => return PostureStatus::Unknown,
+            Reading::Scored(score) => score,
+        };
+        let good = match self.status {
+            PostureStatus::Good => score >= self.config.bad_enter,
+            PostureStatus::Bad => score >= self.config.good_enter,
+            _ => score * 2 >= self.config.good_enter + self.config.bad_enter,
+        };
+        if good { PostureStatus::Good } else { PostureStatus::Bad }
+    }
+}
+
+/// Emitted on `posture-update` (see ARCHITECTURE.md) on every status change.
+#[derive(Debug, Clone, Serialize)]
+pub struct PostureUpdate {
+    pub status: PostureStatus,
+    /// 0-1; None unless Good or Bad.
+    pub score: Option<f32>,
+    pub privacy_hash: String,
+    pub is_calibrated: bool,
+}
+
+impl PostureUpdate {
+    pub fn new(machine: &PostureStateMachine, landmarks: &[Landmark], is_calibrated: bool) -> Self {
+        Self {
+            status: machine.status(),
+            score: machine.score().map(|s| s as f32 / 100.0),
+            privacy_hash: privacy_hash(landmarks),
+            is_calibrated,
+        }
+    }
+}
+
+/// Fingerprint of the landmarks behind a verdict, for the privacy log: proves which frame was
+/// processed without saying anything about it. Quantised so float noise does not matter.
+pub fn privacy_hash(landmarks: &[Landmark]) -> String {
+    let mut hasher = Sha256::new();
+    for lm in landmarks {
+        for v in [lm.x, lm.y, lm.z, lm.visibility] {
+            hasher.update(((v * 1000.0).round() as i32).to_le_bytes());
+        }
+    }
+    format!("{:x}", hasher.finalize())[..16].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::mock::seated_user;
+
+    const FRAME: Duration = Duration::from_millis(66);
+
+    // Feeds `reading` every frame for `duration`, returning every transition
+    fn feed(machine: &mut PostureStateMachine, reading: Reading, start: Instant, duration: Duration) -> Vec<PostureStatus> {
+        let frames = (duration.as_millis() / FRAME.as_millis()) as u32;
+        (0..=frames).filter_map(|i| machine.update(reading, start + FRAME * i)).collect()
+    }
+
+    #[test]
+    fn a_single_noisy_frame_does_not_flip_the_state() {
+        let mut machine = PostureStateMachine::new(StateConfig::default());
+        let start = Instant::now();
+        assert_eq!(feed(&mut machine, Reading::Scored(90), start, Duration::from_secs(2)), vec![PostureStatus::Good]);
+
+        let t = start + Duration::from_secs(3);
+        assert_eq!(machine.update(Reading::Scored(20), t), None);
+        assert_eq!(machine.update(Reading::Unusable, t + FRAME), None);
+        assert_eq!(machine.update(Reading::Absent, t + FRAME * 2), None);
+        assert_eq!(machine.update(Reading::Scored(90), t + FRAME * 3), None);
+        assert_eq!(machine.status(), PostureStatus::Good);
+    }
+
+    #[test]
+    fn scores_inside_the_band_keep_the_current_status() {
+        let mut machine = PostureStateMachine::new(StateConfig::default());
+        let start = Instant::now();
+        feed(&mut machine, Reading::Scored(90), start, Duration::from_secs(2));
+
+        // 78 is below "good" but not below bad_enter
+        let t = start + Duration::from_secs(3);
+        assert!(feed(&mut machine, Reading::Scored(78), t, Duration::from_secs(10)).is_empty());
+        assert_eq!(machine.score(), Some(78));
+
+        let t = t + Duration::from_secs(11);
+        assert_eq!(feed(&mut machine, Reading::Scored(60), t, Duration::from_secs(4)), vec![PostureStatus::Bad]);
+        // and 78 does not make a Bad user Good either
+        let t = t + Duration::from_secs(5);
+        assert!(feed(&mut machine, Reading::Scored(78), t, Duration::from_secs(10)).is_empty());
+    }
+
+    #[test]
+    fn leaving_and_returning() {
+        let mut machine = PostureStateMachine::new(StateConfig::default());
+        let start = Instant::now();
+        feed(&mut machine, Reading::Scored(90), start, Duration::from_secs(2));
+
+        let t = start + Duration::from_secs(3);
+        assert_eq!(feed(&mut machine, Reading::Absent, t, Duration::from_secs(6)), vec![PostureStatus::Away]);
+        assert_eq!(machine.score(), None);
+
+        let t = t + Duration::from_secs(7);
+        assert_eq!(feed(&mut machine, Reading::Scored(50), t, Duration::from_secs(4)), vec![PostureStatus::Bad]);
+    }
+
+    #[test]
+    fn thresholds_come_from_settings() {
+        let settings: HashMap<String, String> = [("state_good_enter", "95"), ("state_away_dwell_ms", "0")]
+            .iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let config = StateConfig::from_settings(&settings);
+        assert_eq!(config.good_enter, 95);
+        assert_eq!(config.bad_enter, 75);
+
+        let mut machine = PostureStateMachine::new(config);
+        assert_eq!(machine.update(Reading::Absent, Instant::now()), Some(PostureStatus::Away));
+    }
+
+    #[test]
+    fn privacy_hash_ignores_float_noise() {
+        let landmarks = seated_user(0.5);
+        let mut jittered = landmarks.clone();
+        jittered[0].x += 1e-5;
+        assert_eq!(privacy_hash(&landmarks), privacy_hash(&jittered));
+        assert_ne!(privacy_hash(&landmarks), privacy_hash(&seated_user(0.6)));
+        assert_eq!(privacy_hash(&landmarks).len(), 16);
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::pose::keypoints::*;
+use crate::pose::{Landmark, PostureAngles};
+use crate::stance::Stance;
+
+// Penalty per pixel of offset, as in postureMath.ts
+const NECK_PENALTY: f32 = 2.0;
+const SHOULDER_PENALTY: f32 = 4.0;
+const SPINE_PENALTY: f32 = 2.0;
+
+/// Angle at which a part scores 100 and angle at which it scores 0, linear in between.
+/// Fixed rather than configurable so scores stay comparable across users and sessions.
+///
+/// | angle                 | 100     | 0       | basis                                         |
+/// |-----------------------|---------|---------|-----------------------------------------------|
+/// | craniovertebral       | >= 50   | <= 35   | forward head posture is usually put below ~48 |
+/// | neck flexion (pitch)  | <= 15   | >= 45   | RULA neck bands: 0-10, 10-20, 20+             |
+/// | shoulder tilt         | <= 3    | >= 12   | a few degrees of asymmetry is normal          |
+/// | trunk inclination     | <= 10   | >= 30   | RULA trunk bands: 0-20, 20-60                 |
+/// | shoulder protraction  | <= 20   | >= 45   | estimate; relaxed sitting already rolls ~15   |
+/// | trunk, standing       | <= 5    | >= 20   | no backrest to lean on; keep near vertical    |
+#[derive(Debug, Clone, Copy)]
+struct Band {
+    full: f32,
+    zero: f32,
+}
+
+const CRANIOVERTEBRAL: Band = Band { full: 50.0, zero: 35.0 };
+const NECK_FLEXION: Band = Band { full: 15.0, zero: 45.0 };
+const SHOULDER_TILT: Band = Band { full: 3.0, zero: 12.0 };
+const TRUNK_INCLINATION: Band = Band { full: 10.0, zero: 30.0 };
+const SHOULDER_PROTRACTION: Band = Band { full: 20.0, zero: 45.0 };
+const STANDING_TRUNK_INCLINATION: Band = Band { full: 5.0, zero: 20.0 };
+
+impl Band {
+    // Works for bands in either direction (CVA gets worse as it falls, the rest as they rise)
+    fn score(&self, angle: f32) -> f32 {
+        clamp(100.0 * (angle - self.zero) / (self.full - self.zero))
+    }
+
+    // With a calibrated baseline, the user's own good posture scores 100 and the band's
+    // width is how far they may drift from it
+    fn score_from(&self, angle: f32, baseline: Option<f32>) -> f32 {
+        match baseline {
+            Some(base) => self.score(self.full + (angle - base)),
+            None => self.score(angle),
+        }
+    }
+}
+
+/// How parts are scored: "angles" (default) or "pixels", the original postureMath.ts formulas
+/// kept for comparison with the webview.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScoringMethod {
+    Angles,
+    Pixels,
+}
+
+/// Weights and thresholds, read from the settings table:
+///   score_weight_neck (0.4), score_weight_spine (0.4), score_weight_shoulders (0.2)
+///   score_good_threshold: total above this counts as good posture (80)
+///   score_min_visibility: landmarks below this count as missing for "pixels" (0.3)
+///   score_method: "angles" (default) or "pixels"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoringConfig {
+    pub neck_weight: f32,
+    pub spine_weight: f32,
+    pub shoulders_weight: f32,
+    pub good_threshold: f32,
+    pub min_visibility: f32,
+    pub method: ScoringMethod,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self { neck_weight: 0.4, spine_weight: 0.4, shoulders_weight: 0.2, good_threshold: 80.0, min_visibility: 0.3,
+               method: ScoringMethod::Angles }
+    }
+}
+
+impl ScoringConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let d = Self::default();
+        let get = |key: &str| settings.get(key).and_then(|v| v.parse::<f32>().ok());
+        Self {
+            neck_weight: get("score_weight_neck").unwrap_or(d.neck_weight),
+            spine_weight: get("score_weight_spine").unwrap_or(d.spine_weight),
+            shoulders_weight: get("score_weight_shoulders").unwrap_or(d.shoulders_weight),
+            good_threshold: get("score_good_threshold").unwrap_or(d.good_threshold),
+            min_visibility: get("score_min_visibility").unwrap_or(d.min_visibility),
+            method: match settings.get("score_method").map(String::as_str) {
+                Some("pixels") => ScoringMethod::Pixels,
+                _ => d.method,
+            },
+        }
+    }
+}
+
+/// Same shape as `PostureMetrics` in postureMath.ts: each part 0-100, rounded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct PostureScore {
+    pub total: i64,
+    pub neck: i64,
+    pub shoulders: i64,
+    pub spine: i64,
+    pub is_good: bool,
+}
+
+impl PostureScore {
+    // What the webview shows when the key points are missing: bad, so it prompts the user
+    const MISSING: Self = Self { total: 0, neck: 0, shoulders: 0, spine: 0, is_good: false };
+
+    fn from_parts(neck: f32, shoulders: f32, spine: f32, config: &ScoringConfig) -> Self {
+        let total = neck * config.neck_weight + spine * config.spine_weight + shoulders * config.shoulders_weight;
+        Self {
+            total: total.round() as i64,
+            neck: neck.round() as i64,
+            shoulders: shoulders.round() as i64,
+            spine: spine.round() as i64,
+            is_good: total > config.good_threshold,
+        }
+    }
+}
+
+/// Scores one frame with the configured method. `baseline` holds the calibrated angles, if any.
+pub fn score(landmarks: &[Landmark], angles: &PostureAngles, baseline: Option<&PostureAngles>, stance: Stance,
+             frame_size: (u32, u32), config: &ScoringConfig) -> PostureScore {
+    match config.method {
+        ScoringMethod::Angles => score_angles(angles, baseline, stance, config),
+        ScoringMethod::Pixels => score_pixels(landmarks, frame_size, config),
+    }
+}
+
+/// Neck from craniovertebral angle and flexion, shoulders from tilt and protraction, spine
+/// from trunk inclination (shoulders stand in when the hips are hidden, as in the webview).
+/// A part averages whichever of its angles could be measured. Angles the baseline has are
+/// scored relative to it, the rest against the absolute bands. Standing, the trunk is held to
+/// its own band and never to the (seated) baseline.
+pub fn score_angles(angles: &PostureAngles, baseline: Option<&PostureAngles>, stance: Stance, config: &ScoringConfig) -> PostureScore {
+    let base = |f: fn(&PostureAngles) -> Option<f32>| baseline.and_then(f);
+    let neck = mean_of(&[
+        angles.craniovertebral_deg.map(|a| CRANIOVERTEBRAL.score_from(a, base(|b| b.craniovertebral_deg))),
+        angles.neck_flexion_deg.map(|a| NECK_FLEXION.score_from(a, base(|b| b.neck_flexion_deg))),
+    ]);
+    let shoulders = mean_of(&[
+        angles.shoulder_tilt_deg.map(|a| SHOULDER_TILT.score_from(a, base(|b| b.shoulder_tilt_deg))),
+        angles.shoulder_protraction_deg.map(|a| SHOULDER_PROTRACTION.score_from(a, base(|b| b.shoulder_protraction_deg))),
+    ]);
+
+    let (Some(neck), Some(shoulders)) = (neck, shoulders) else {
+        return PostureScore::MISSING;
+    };
+    let spine = angles.trunk_inclination_deg
+        .map(|a| match stance {
+            Stance::Sitting => TRUNK_INCLINATION.score_from(a, base(|b| b.trunk_inclination_deg)),
+            Stance::Standing => STANDING_TRUNK_INCLINATION.score(a),
+        })
+        .unwrap_or(shoulders);
+    PostureScore::from_parts(neck, shoulders, spine, config)
+}
+
+/// Port of `calculatePostureMetrics`. The formulas work in pixels, so `frame_size` turns
+/// our normalised landmarks back into the coordinates the TFJS keypoints used.
+pub fn score_pixels(landmarks: &[Landmark], frame_size: (u32, u32), config: &ScoringConfig) -> PostureScore {
+    let (w, h) = (frame_size.0 as f32, frame_size.1 as f32);
+    let point = |i: usize| landmarks.get(i)
+        .filter(|lm| lm.visibility >= config.min_visibility)
+        .map(|lm| (lm.x * w, lm.y * h));
+
+    let (Some(nose), Some(left_ear), Some(right_ear), Some(left_shoulder), Some(right_shoulder)) =
+        (point(NOSE), point(LEFT_EAR), point(RIGHT_EAR), point(LEFT_SHOULDER), point(RIGHT_SHOULDER))
+    else {
+        return PostureScore::MISSING;
+    };
+
+    // 1. Neck: ears stacked over shoulders
+    let ear_x = (left_ear.0 + right_ear.0) / 2.0;
+    let shoulder_x = (left_shoulder.0 + right_shoulder.0) / 2.0;
+    let neck = clamp(100.0 - (ear_x - shoulder_x).abs() * NECK_PENALTY);
+
+    // 2. Shoulders: level
+    let shoulders = clamp(100.0 - (left_shoulder.1 - right_shoulder.1).abs() * SHOULDER_PENALTY);
+
+    // 3. Spine: nose over hips; webcam close-ups often cut the hips, then shoulders stand in
+    let spine = match (point(LEFT_HIP), point(RIGHT_HIP)) {
+        (Some(left_hip), Some(right_hip)) => clamp(100.0 - (nose.0 - (left_hip.0 + right_hip.0) / 2.0).abs() * SPINE_PENALTY),
+        _ => shoulders,
+    };
+
+    PostureScore::from_parts(neck, shoulders, spine, config)
+}
+
+fn mean_of(scores: &[Option<f32>]) -> Option<f32> {
+    let known: Vec<f32> = scores.iter().flatten().copied().collect();
+    (!known.is_empty()).then(|| known.iter().sum::<f32>() / known.len() as f32)
+}
+
+fn clamp(v: f32) -> f32 {
+    v.clamp(0.0, 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::mock::seated_user;
+    use crate::pose::HeadPose;
+
+    const FRAME: (u32, u32) = (640, 480);
+
+    #[test]
+    fn upright_user_scores_full_marks() {
+        let result = score_pixels(&seated_user(0.5), FRAME, &ScoringConfig::default());
+        assert_eq!(result, PostureScore { total: 100, neck: 100, shoulders: 100, spine: 100, is_good: true });
+    }
+
+    #[test]
+    fn matches_the_webview_formulas() {
+        let mut landmarks = seated_user(0.5);
+        // Head 15 px right of the shoulders, left shoulder 7.5 px low, nose 15 px off the hips
+        landmarks[LEFT_EAR].x += 15.0 / 640.0;
+        landmarks[RIGHT_EAR].x += 15.0 / 640.0;
+        landmarks[NOSE].x += 15.0 / 640.0;
+        landmarks[LEFT_SHOULDER].y += 7.5 / 480.0;
+
+        let result = score_pixels(&landmarks, FRAME, &ScoringConfig::default());
+        assert_eq!((result.neck, result.shoulders, result.spine), (70, 70, 70));
+        assert_eq!(result.total, 70);
+        assert!(!result.is_good);
+    }
+
+    #[test]
+    fn hidden_hips_fall_back_to_shoulders() {
+        let mut landmarks = seated_user(0.5);
+        landmarks[LEFT_SHOULDER].y += 5.0 / 480.0;
+        landmarks[LEFT_HIP].visibility = 0.1;
+
+        let result = score_pixels(&landmarks, FRAME, &ScoringConfig::default());
+        assert_eq!(result.spine, result.shoulders);
+    }
+
+    #[test]
+    fn missing_ears_score_zero() {
+        let mut landmarks = seated_user(0.5);
+        landmarks[RIGHT_EAR].visibility = 0.0;
+        assert_eq!(score_pixels(&landmarks, FRAME, &ScoringConfig::default()), PostureScore::MISSING);
+        assert_eq!(score_pixels(&[], FRAME, &ScoringConfig::default()), PostureScore::MISSING);
+    }
+
+    #[test]
+    fn weights_and_threshold_come_from_settings() {
+        let settings: HashMap<String, String> = [
+            ("score_weight_neck", "1.0"), ("score_weight_spine", "0"), ("score_weight_shoulders", "0"),
+            ("score_good_threshold", "50"),
+        ].iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let config = ScoringConfig::from_settings(&settings);
+
+        let mut landmarks = seated_user(0.5);
+        landmarks[LEFT_EAR].x += 20.0 / 640.0;
+        landmarks[RIGHT_EAR].x += 20.0 / 640.0;
+        let result = score_pixels(&landmarks, FRAME, &config);
+        assert_eq!(result.total, result.neck);
+        assert!(result.is_good);
+    }
+
+    fn measure(landmarks: &[Landmark]) -> PostureAngles {
+        let head = HeadPose::estimate(landmarks, 640.0 / 480.0);
+        PostureAngles::measure(landmarks, None, head.as_ref(), 640.0 / 480.0)
+    }
+
+    #[test]
+    fn upright_user_scores_full_marks_on_angles() {
+        let angles = measure(&seated_user(0.5));
+        assert_eq!(angles.shoulder_tilt_deg, Some(0.0));
+        assert_eq!(score_angles(&angles, None, Stance::Sitting, &ScoringConfig::default()).total, 100);
+    }
+
+    #[test]
+    fn angle_scores_do_not_depend_on_camera_distance() {
+        // Same posture, user twice as far away: every landmark halves its offset from centre
+        let mut near = seated_user(0.5);
+        near[LEFT_SHOULDER].y += 0.04;
+        let far: Vec<Landmark> = near.iter()
+            .map(|lm| Landmark { x: 0.5 + (lm.x - 0.5) / 2.0, y: 0.5 + (lm.y - 0.5) / 2.0, ..lm.clone() })
+            .collect();
+
+        let config = ScoringConfig::default();
+        assert_eq!(score_angles(&measure(&near), None, Stance::Sitting, &config), score_angles(&measure(&far), None, Stance::Sitting, &config));
+        assert!(score_pixels(&near, FRAME, &config) != score_pixels(&far, FRAME, &config));
+    }
+
+    #[test]
+    fn bands_interpolate_between_thresholds() {
+        assert_eq!(CRANIOVERTEBRAL.score(60.0), 100.0);
+        assert_eq!(CRANIOVERTEBRAL.score(42.5), 50.0);
+        assert_eq!(CRANIOVERTEBRAL.score(20.0), 0.0);
+        assert_eq!(NECK_FLEXION.score(30.0), 50.0);
+        assert_eq!(SHOULDER_TILT.score(1.0), 100.0);
+    }
+
+    #[test]
+    fn missing_angles_score_zero_and_hidden_hips_borrow_shoulders() {
+        assert_eq!(score_angles(&PostureAngles::default(), None, Stance::Sitting, &ScoringConfig::default()), PostureScore::MISSING);
+
+        let angles = PostureAngles {
+            craniovertebral_deg: Some(55.0),
+            shoulder_tilt_deg: Some(7.5),
+            ..PostureAngles::default()
+        };
+        let result = score_angles(&angles, None, Stance::Sitting, &ScoringConfig::default());
+        assert_eq!((result.neck, result.shoulders, result.spine), (100, 50, 50));
+    }
+
+    #[test]
+    fn calibrated_scores_are_relative_to_the_baseline() {
+        // This user's comfortable upright sits at a CVA of 42, which scores 47 in absolute terms
+        let baseline = PostureAngles { craniovertebral_deg: Some(42.0), shoulder_tilt_deg: Some(4.0), ..PostureAngles::default() };
+        let config = ScoringConfig::default();
+
+        let result = score_angles(&baseline, Some(&baseline), Stance::Sitting, &config);
+        assert_eq!((result.neck, result.shoulders), (100, 100));
+
+        let slumped = PostureAngles { craniovertebral_deg: Some(34.5), ..baseline };
+        assert_eq!(score_angles(&slumped, Some(&baseline), Stance::Sitting, &config).neck, 50);
+    }
+
+    #[test]
+    fn standing_trunk_has_its_own_band() {
+        let angles = PostureAngles { craniovertebral_deg: Some(55.0), shoulder_tilt_deg: Some(0.0),
+                                     trunk_inclination_deg: Some(10.0), ..PostureAngles::default() };
+        let baseline = PostureAngles { trunk_inclination_deg: Some(10.0), ..angles };
+        let config = ScoringConfig::default();
+
+        assert_eq!(score_angles(&angles, Some(&baseline), Stance::Sitting, &config).spine, 100);
+        assert_eq!(score_angles(&angles, Some(&baseline), Stance::Standing, &config).spine, 67);
+    }
+}
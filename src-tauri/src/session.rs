@@ -0,0 +1,337 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use crate::commands::{self, SessionSummary};
+use crate::pose::keypoints::*;
+use crate::pose::Landmark;
+use crate::posture_state::PostureStatus;
+use crate::stance::Stance;
+use crate::state::AppState;
+
+// Head and shoulders: what a webcam at a desk sees of someone sitting there
+const PRESENCE_POINTS: [usize; 5] = [NOSE, LEFT_EAR, RIGHT_EAR, LEFT_SHOULDER, RIGHT_SHOULDER];
+
+/// Presence and automatic sessions, read from the settings table:
+///   presence_min_visibility: mean head/shoulder visibility that counts as someone there (0.3)
+///   session_end_after_sec: away this long ends the session (300)
+///   session_min_sec: sessions with less present time than this are not stored (60)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionConfig {
+    pub min_visibility: f32,
+    pub end_after: Duration,
+    pub min_duration: Duration,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self { min_visibility: 0.3, end_after: Duration::from_secs(300), min_duration: Duration::from_secs(60) }
+    }
+}
+
+impl SessionConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let d = Self::default();
+        let get = |key: &str| settings.get(key).and_then(|v| v.parse::<f32>().ok());
+        let secs = |key: &str| get(key).map(|s| Duration::from_secs_f32(s.max(0.0)));
+        Self {
+            min_visibility: get("presence_min_visibility").unwrap_or(d.min_visibility),
+            end_after: secs("session_end_after_sec").unwrap_or(d.end_after),
+            min_duration: secs("session_min_sec").unwrap_or(d.min_duration),
+        }
+    }
+}
+
+/// Whether someone is at the desk. Looser than the quality check: a user half out of frame
+/// is present, just not scorable.
+pub fn is_present(landmarks: &[Landmark], config: &SessionConfig) -> bool {
+    if landmarks.is_empty() { return false; }
+    let total: f32 = PRESENCE_POINTS.iter().map(|&i| landmarks.get(i).map_or(0.0, |lm| lm.visibility)).sum();
+    total / PRESENCE_POINTS.len() as f32 >= config.min_visibility
+}
+
+/// A stretch of time the user was away during a session; stored in the session breakdown.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PresenceGap {
+    pub start: String,
+    pub end: String,
+    pub duration_sec: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionEvent {
+    Started { id: String },
+    Paused,
+    Resumed(PresenceGap),
+    /// Summary without the backend breakdown; the caller merges that in before storing.
+    /// None when the session was too short to keep.
+    Ended(Option<SessionSummary>),
+}
+
+#[derive(Debug)]
+struct ActiveSession {
+    id: String,
+    started: DateTime<Local>,
+    last_tick: Instant,
+    good: Duration,
+    bad: Duration,
+    // Present but not scorable (dark, half out of frame)
+    unscored: Duration,
+    score_sum: f64,
+    score_count: u64,
+    standing: Duration,
+    stance: Stance,
+    stance_changes: i64,
+    // When the posture state went Away, on both clocks
+    away_since: Option<(Instant, DateTime<Local>)>,
+    // Where the session ends if the user never comes back
+    last_present: DateTime<Local>,
+}
+
+impl ActiveSession {
+    fn present(&self) -> Duration {
+        self.good + self.bad + self.unscored
+    }
+
+    fn summary(&self, end: DateTime<Local>) -> SessionSummary {
+        SessionSummary {
+            id: self.id.clone(),
+            start_time: self.started.to_rfc3339(),
+            end_time: end.to_rfc3339(),
+            duration_sec: self.present().as_secs() as i64,
+            avg_score: if self.score_count == 0 { 0 } else { (self.score_sum / self.score_count as f64).round() as i64 },
+            good_time_sec: self.good.as_secs() as i64,
+            bad_time_sec: self.bad.as_secs() as i64,
+            breakdown_json: "{}".to_string(),
+            model_version: None,
+            standing_sec: self.standing.as_secs() as i64,
+            stance_changes: self.stance_changes,
+        }
+    }
+}
+
+/// Turns the posture state into sessions: starts one when the user shows up, pauses the
+/// clock while they are Away, and ends it after `end_after` of absence. `duration_sec`
+/// only counts time the user was there.
+#[derive(Debug)]
+pub struct SessionTracker {
+    config: SessionConfig,
+    active: Option<ActiveSession>,
+}
+
+impl SessionTracker {
+    pub fn new(config: SessionConfig) -> Self {
+        Self { config, active: None }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Call once per processed frame with the debounced status and stance and, when scored,
+    /// the score.
+    pub fn update(&mut self, status: PostureStatus, score: Option<i64>, stance: Stance, now: Instant, wall: DateTime<Local>)
+                  -> Option<SessionEvent> {
+        let Some(session) = self.active.as_mut() else {
+            if status == PostureStatus::Away { return None; }
+            let id = uuid::Uuid::new_v4().to_string();
+            self.active = Some(ActiveSession {
+                id: id.clone(), started: wall, last_tick: now,
+                good: Duration::ZERO, bad: Duration::ZERO, unscored: Duration::ZERO,
+                score_sum: 0.0, score_count: 0, standing: Duration::ZERO, stance, stance_changes: 0,
+                away_since: None, last_present: wall,
+            });
+            return Some(SessionEvent::Started { id });
+        };
+
+        let elapsed = now.duration_since(session.last_tick);
+        session.last_tick = now;
+
+        if status == PostureStatus::Away {
+            let (since, _) = *session.away_since.get_or_insert((now, wall));
+            if now.duration_since(since) >= self.config.end_after {
+                return Some(SessionEvent::Ended(self.finish()));
+            }
+            return (since == now).then_some(SessionEvent::Paused);
+        }
+
+        match status {
+            PostureStatus::Good => session.good += elapsed,
+            PostureStatus::Bad => session.bad += elapsed,
+            _ => session.unscored += elapsed,
+        }
+        if let Some(score) = score {
+            session.score_sum += score as f64;
+            session.score_count += 1;
+        }
+        if stance == Stance::Standing {
+            session.standing += elapsed;
+        }
+        if stance != session.stance {
+            session.stance = stance;
+            session.stance_changes += 1;
+        }
+        session.last_present = wall;
+
+        let (since, start) = session.away_since.take()?;
+        Some(SessionEvent::Resumed(PresenceGap {
+            start: start.to_rfc3339(),
+            end: wall.to_rfc3339(),
+            duration_sec: now.duration_since(since).as_secs() as i64,
+        }))
+    }
+
+    /// Ends the current session (tracking stopped, or the user left for good). The end time
+    /// is the last time the user was seen.
+    pub fn finish(&mut self) -> Option<SessionSummary> {
+        let session = self.active.take()?;
+        (session.present() >= self.config.min_duration).then(|| session.summary(session.last_present))
+    }
+}
+
+/// Stores a finished session with what the backend measured during it, and starts the
+/// next breakdown clean.
+pub fn store(state: &AppState, mut session: SessionSummary) -> Result<SessionSummary, String> {
+    session.model_version = state.pose_engine.active_model().map(|m| m.version_tag());
+    if let Ok(mut breakdown) = state.breakdown.lock() {
+        session.breakdown_json = std::mem::take(&mut *breakdown).merge_into(&session.breakdown_json);
+    }
+    let db_lock = state.db.lock().map_err(|_| "Failed to lock DB")?;
+    let conn = db_lock.as_ref().ok_or("Database not initialized")?;
+    commands::insert_session(conn, &session)?;
+    Ok(session)
+}
+
+/// Inference-worker side: keeps the breakdown in step with the session and tells the webview.
+/// Emits `session_started` (id), `session_paused`, `session_resumed` (the gap) and
+/// `session_ended` (the stored summary, or null when it was too short to keep).
+pub fn handle_event(app_handle: &AppHandle, state: &AppState, event: SessionEvent) {
+    match event {
+        SessionEvent::Started { id } => {
+            // Anything measured before the user sat down belongs to no session
+            if let Ok(mut breakdown) = state.breakdown.lock() {
+                *breakdown = Default::default();
+            }
+            println!("🟢 Session started");
+            let _ = app_handle.emit("session_started", id);
+        }
+        SessionEvent::Paused => {
+            let _ = app_handle.emit("session_paused", ());
+        }
+        SessionEvent::Resumed(gap) => {
+            if let Ok(mut breakdown) = state.breakdown.lock() {
+                breakdown.record_gap(gap.clone());
+            }
+            let _ = app_handle.emit("session_resumed", gap);
+        }
+        SessionEvent::Ended(session) => {
+            let stored = match session.map(|s| store(state, s)).transpose() {
+                Ok(stored) => stored,
+                Err(e) => {
+                    println!("⚠️ Session not saved: {}", e);
+                    None
+                }
+            };
+            match &stored {
+                Some(s) => println!("🔴 Session ended: {}s present, avg {}", s.duration_sec, s.avg_score),
+                None => println!("🔴 Session ended, not stored"),
+            }
+            let _ = app_handle.emit("session_ended", stored);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::mock::seated_user;
+
+    const TICK: Duration = Duration::from_secs(1);
+
+    fn config() -> SessionConfig {
+        SessionConfig { min_visibility: 0.3, end_after: Duration::from_secs(60), min_duration: Duration::from_secs(10) }
+    }
+
+    // Runs `status` once a second for `secs`, returning the events
+    fn run(tracker: &mut SessionTracker, clock: &mut (Instant, DateTime<Local>), status: PostureStatus, score: Option<i64>, secs: u32)
+           -> Vec<SessionEvent> {
+        run_in(tracker, clock, status, score, Stance::Sitting, secs)
+    }
+
+    fn run_in(tracker: &mut SessionTracker, clock: &mut (Instant, DateTime<Local>), status: PostureStatus, score: Option<i64>,
+              stance: Stance, secs: u32) -> Vec<SessionEvent> {
+        (0..secs).filter_map(|_| {
+            clock.0 += TICK;
+            clock.1 += chrono::Duration::seconds(1);
+            tracker.update(status, score, stance, clock.0, clock.1)
+        }).collect()
+    }
+
+    #[test]
+    fn presence_comes_from_head_and_shoulder_visibility() {
+        let mut landmarks = seated_user(0.5);
+        assert!(is_present(&landmarks, &config()));
+        for lm in landmarks.iter_mut() {
+            lm.visibility = 0.1;
+        }
+        assert!(!is_present(&landmarks, &config()));
+        assert!(!is_present(&[], &config()));
+    }
+
+    #[test]
+    fn time_away_pauses_accounting() {
+        let mut tracker = SessionTracker::new(config());
+        let mut clock = (Instant::now(), Local::now());
+
+        let events = run(&mut tracker, &mut clock, PostureStatus::Good, Some(90), 21);
+        assert!(matches!(events[0], SessionEvent::Started { .. }));
+        assert_eq!(run(&mut tracker, &mut clock, PostureStatus::Away, None, 30), vec![SessionEvent::Paused]);
+
+        let events = run(&mut tracker, &mut clock, PostureStatus::Bad, Some(50), 11);
+        let SessionEvent::Resumed(gap) = &events[0] else { panic!("expected a resume, got {:?}", events) };
+        assert_eq!(gap.duration_sec, 30);
+
+        let session = tracker.finish().unwrap();
+        // The first frame only starts the clock; the frame after the gap counts as Bad
+        assert_eq!((session.good_time_sec, session.bad_time_sec, session.duration_sec), (20, 11, 31));
+        assert_eq!(session.avg_score, 76);
+        assert!(!tracker.is_active());
+    }
+
+    #[test]
+    fn long_absence_ends_the_session_and_return_starts_a_new_one() {
+        let mut tracker = SessionTracker::new(config());
+        let mut clock = (Instant::now(), Local::now());
+
+        let first = run(&mut tracker, &mut clock, PostureStatus::Good, Some(90), 31);
+        let events = run(&mut tracker, &mut clock, PostureStatus::Away, None, 61);
+        let Some(SessionEvent::Ended(Some(session))) = events.last() else { panic!("expected an end, got {:?}", events) };
+        assert_eq!(session.duration_sec, 30);
+
+        let events = run(&mut tracker, &mut clock, PostureStatus::Unknown, None, 1);
+        let (SessionEvent::Started { id: a }, SessionEvent::Started { id: b }) = (&first[0], &events[0]) else { panic!() };
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn standing_time_and_changes_are_counted() {
+        let mut tracker = SessionTracker::new(config());
+        let mut clock = (Instant::now(), Local::now());
+
+        run_in(&mut tracker, &mut clock, PostureStatus::Good, Some(90), Stance::Sitting, 11);
+        run_in(&mut tracker, &mut clock, PostureStatus::Good, Some(90), Stance::Standing, 20);
+        run_in(&mut tracker, &mut clock, PostureStatus::Good, Some(90), Stance::Sitting, 5);
+
+        let session = tracker.finish().unwrap();
+        assert_eq!((session.standing_sec, session.stance_changes), (20, 2));
+    }
+
+    #[test]
+    fn short_sessions_are_dropped() {
+        let mut tracker = SessionTracker::new(config());
+        let mut clock = (Instant::now(), Local::now());
+        run(&mut tracker, &mut clock, PostureStatus::Good, Some(90), 5);
+        assert_eq!(tracker.finish(), None);
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use crate::calibration::Baseline;
+use crate::pose::keypoints::*;
+use crate::pose::Landmark;
+
+// Votes for standing, out of 1. Knees are hidden under the desk when sitting; hips often are
+const KNEES_VISIBLE: f32 = 0.4;
+const HIPS_VISIBLE: f32 = 0.2;
+// Standing users step back from the desk, so the shoulders look narrower than calibrated...
+const SMALLER: f32 = 0.2;
+const SMALLER_RATIO: f32 = 0.85;
+// ...and the head rises, here by half a calibrated shoulder width
+const HEAD_RAISED: f32 = 0.4;
+const HEAD_RISE: f32 = 0.5;
+const STANDING_VOTES: f32 = 0.5;
+
+/// Sitting or standing at the desk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stance {
+    #[default]
+    Sitting,
+    Standing,
+}
+
+impl Stance {
+    /// What one frame looks like. The calibrated baseline is assumed to be seated; without one
+    /// only hip and knee visibility count. None when the shoulders are not visible.
+    pub fn classify(landmarks: &[Landmark], baseline: Option<&Baseline>) -> Option<Self> {
+        let width = shoulder_frame(landmarks)?.width;
+
+        let mut votes = 0.0;
+        if visible(landmarks, LEFT_KNEE).is_some() || visible(landmarks, RIGHT_KNEE).is_some() {
+            votes += KNEES_VISIBLE;
+        }
+        if visible(landmarks, LEFT_HIP).is_some() && visible(landmarks, RIGHT_HIP).is_some() {
+            votes += HIPS_VISIBLE;
+        }
+        if let Some((baseline, base_width)) = baseline.and_then(|b| Some((b, shoulder_frame(&b.landmarks)?.width))) {
+            if width / base_width < SMALLER_RATIO {
+                votes += SMALLER;
+            }
+            if let (Some(head), Some(base_head)) = (head_y(landmarks), head_y(&baseline.landmarks)) {
+                if (base_head - head) / base_width > HEAD_RISE {
+                    votes += HEAD_RAISED;
+                }
+            }
+        }
+        Some(if votes >= STANDING_VOTES { Stance::Standing } else { Stance::Sitting })
+    }
+}
+
+fn head_y(landmarks: &[Landmark]) -> Option<f32> {
+    visible(landmarks, NOSE).map(|lm| lm.y)
+}
+
+/// Read from the settings table:
+///   stance_dwell_sec: a new stance must hold this long to count, so bending to pick
+///   something up is not a stand (10)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StanceConfig {
+    pub dwell: Duration,
+}
+
+impl Default for StanceConfig {
+    fn default() -> Self {
+        Self { dwell: Duration::from_secs(10) }
+    }
+}
+
+impl StanceConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let d = Self::default();
+        let get = |key: &str| settings.get(key).and_then(|v| v.parse::<f32>().ok());
+        Self { dwell: get("stance_dwell_sec").map(|s| Duration::from_secs_f32(s.max(0.0))).unwrap_or(d.dwell) }
+    }
+}
+
+/// Debounces per-frame stances. Starts out Sitting.
+#[derive(Debug)]
+pub struct StanceTracker {
+    config: StanceConfig,
+    stance: Stance,
+    pending: Option<(Stance, Instant)>,
+}
+
+impl StanceTracker {
+    pub fn new(config: StanceConfig) -> Self {
+        Self { config, stance: Stance::Sitting, pending: None }
+    }
+
+    pub fn stance(&self) -> Stance {
+        self.stance
+    }
+
+    /// Returns the new stance on a change. Frames that could not be classified keep whatever
+    /// is pending.
+    pub fn update(&mut self, observed: Option<Stance>, now: Instant) -> Option<Stance> {
+        let observed = observed?;
+        if observed == self.stance {
+            self.pending = None;
+            return None;
+        }
+        let since = match self.pending {
+            Some((pending, since)) if pending == observed => since,
+            _ => now,
+        };
+        if now.duration_since(since) < self.config.dwell {
+            self.pending = Some((observed, since));
+            return None;
+        }
+        self.stance = observed;
+        self.pending = None;
+        Some(observed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::mock::seated_user;
+    use crate::pose::PostureAngles;
+
+    fn baseline() -> Baseline {
+        // Seated at the desk: hips and knees hidden
+        let mut seated = seated_user(0.5);
+        seated[LEFT_HIP].visibility = 0.1;
+        seated[RIGHT_HIP].visibility = 0.1;
+        let samples: Vec<_> = (0..20).map(|_| (seated.clone(), PostureAngles::default())).collect();
+        Baseline::from_samples(&samples, None).unwrap()
+    }
+
+    // Standing a step back: smaller, head higher, knees in view
+    fn standing() -> Vec<Landmark> {
+        let mut landmarks: Vec<Landmark> = seated_user(0.5).into_iter()
+            .map(|lm| Landmark { y: lm.y * 0.8 - 0.1, x: 0.5 + (lm.x - 0.5) * 0.8, ..lm })
+            .collect();
+        landmarks[LEFT_KNEE] = Landmark { x: 0.55, y: 0.95, z: 0.0, visibility: 0.8 };
+        landmarks
+    }
+
+    #[test]
+    fn standing_is_told_apart_from_sitting() {
+        let baseline = baseline();
+        assert_eq!(Stance::classify(&baseline.landmarks, Some(&baseline)), Some(Stance::Sitting));
+        assert_eq!(Stance::classify(&standing(), Some(&baseline)), Some(Stance::Standing));
+        // Without a baseline the knees and hips still give it away
+        assert_eq!(Stance::classify(&standing(), None), Some(Stance::Standing));
+        assert_eq!(Stance::classify(&[], None), None);
+    }
+
+    #[test]
+    fn stance_changes_need_to_hold() {
+        let mut tracker = StanceTracker::new(StanceConfig::default());
+        let start = Instant::now();
+        assert_eq!(tracker.update(Some(Stance::Standing), start), None);
+        assert_eq!(tracker.update(None, start + Duration::from_secs(5)), None);
+        assert_eq!(tracker.update(Some(Stance::Standing), start + Duration::from_secs(10)), Some(Stance::Standing));
+
+        // Sitting down for a moment does not count
+        assert_eq!(tracker.update(Some(Stance::Sitting), start + Duration::from_secs(11)), None);
+        assert_eq!(tracker.update(Some(Stance::Standing), start + Duration::from_secs(12)), None);
+        assert_eq!(tracker.stance(), Stance::Standing);
+    }
+}
@@ -1,17 +1,26 @@
 use std::sync::{Mutex, Arc};
+use std::thread::JoinHandle;
 use nokhwa::Camera;
 use rusqlite::Connection;
 
 // 1. Import the AI Module
 use crate::pose::PoseEngine;
+use crate::breakdown::SessionBreakdown;
+use crate::calibration::CalibrationState;
 
 // THE PRIVACY ENGINE STATE
 #[derive(Clone)] // Now we can clone the state!
 pub struct AppState {
     pub db: Arc<Mutex<Option<Connection>>>,
-    pub camera: Arc<Mutex<Option<Camera>>>, 
+    pub camera: Arc<Mutex<Option<Camera>>>,
     // 2. Add the Brain here
-    pub pose_engine: Arc<PoseEngine>, 
+    pub pose_engine: Arc<PoseEngine>,
+    // 3. Background capture thread (see tracking.rs)
+    pub tracker: Arc<Mutex<Option<JoinHandle<()>>>>,
+    // 4. What tracking measured since the last saved session
+    pub breakdown: Arc<Mutex<SessionBreakdown>>,
+    // 5. Personal baseline and any capture in progress (see calibration.rs)
+    pub calibration: Arc<Mutex<CalibrationState>>,
 }
 
 // Initialize with everything OFF (Privacy by Default)
@@ -21,7 +30,10 @@ impl Default for AppState {
             db: Arc::new(Mutex::new(None)),
             camera: Arc::new(Mutex::new(None)),
             // 3. Initialize the Brain
-            pose_engine: Arc::new(PoseEngine::new()), 
+            pose_engine: Arc::new(PoseEngine::new()),
+            tracker: Arc::new(Mutex::new(None)),
+            breakdown: Arc::new(Mutex::new(SessionBreakdown::default())),
+            calibration: Arc::new(Mutex::new(CalibrationState::default())),
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use crate::pose::keypoints::*;
+use crate::pose::Landmark;
+
+// Head and shoulders only: typing hands move all the time without the posture changing
+const MOTION_POINTS: [usize; 5] = [NOSE, LEFT_EAR, RIGHT_EAR, LEFT_SHOULDER, RIGHT_SHOULDER];
+// Motion energy is smoothed over about this long
+const ENERGY_WINDOW: f32 = 2.0;
+// Shorter still periods are not worth keeping in the session stats
+const MIN_STILL_PERIOD: Duration = Duration::from_secs(60);
+
+/// Read from the settings table:
+///   stillness_nudge_min: minutes in the same pose before a "move a bit" nudge, repeated
+///   as long as the user stays put (30)
+///   stillness_tolerance: how far (in shoulder widths) the head and shoulders may drift and
+///   still count as the same pose (0.15)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StillnessConfig {
+    pub nudge_after: Duration,
+    pub tolerance: f32,
+}
+
+impl Default for StillnessConfig {
+    fn default() -> Self {
+        Self { nudge_after: Duration::from_secs(30 * 60), tolerance: 0.15 }
+    }
+}
+
+impl StillnessConfig {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let d = Self::default();
+        let get = |key: &str| settings.get(key).and_then(|v| v.parse::<f32>().ok());
+        Self {
+            nudge_after: get("stillness_nudge_min").map(|m| Duration::from_secs_f32(m.max(1.0) * 60.0)).unwrap_or(d.nudge_after),
+            tolerance: get("stillness_tolerance").unwrap_or(d.tolerance),
+        }
+    }
+}
+
+/// Emitted on `movement_nudge`, whatever the posture score.
+#[derive(Debug, Clone, Serialize)]
+pub struct MovementNudge {
+    pub still_minutes: u64,
+    pub message: String,
+}
+
+/// What one frame changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stillness {
+    /// Smoothed head-and-shoulder speed, shoulder widths per second. None until two frames
+    /// could be compared.
+    pub motion_energy: Option<f32>,
+    /// How long the user has held the current pose.
+    pub still_for: Duration,
+    /// A long still period that this frame ended.
+    pub ended: Option<Duration>,
+    pub nudge: bool,
+}
+
+// Mean distance between the motion points visible in both, in shoulder widths of `from`
+fn displacement(from: &[Landmark], to: &[Landmark]) -> Option<f32> {
+    let point = |lms: &[Landmark], i: usize| visible(lms, i).map(|lm| (lm.x, lm.y));
+    let width = shoulder_frame(from)?.width;
+
+    let moves: Vec<f32> = MOTION_POINTS.iter()
+        .filter_map(|&i| Some((point(from, i)?, point(to, i)?)))
+        .map(|(a, b)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt() / width)
+        .collect();
+    (!moves.is_empty()).then(|| moves.iter().sum::<f32>() / moves.len() as f32)
+}
+
+/// Tracks how long the user has held a near-identical pose. Frame-to-frame motion alone
+/// would miss a slow slide down the chair, so the pose is compared with where the still
+/// period started.
+#[derive(Debug)]
+pub struct StillnessMonitor {
+    config: StillnessConfig,
+    anchor: Option<(Vec<Landmark>, Instant)>,
+    previous: Option<(Vec<Landmark>, Instant)>,
+    energy: Option<f32>,
+    nudged_at: Option<Instant>,
+}
+
+impl StillnessMonitor {
+    pub fn new(config: StillnessConfig) -> Self {
+        Self { config, anchor: None, previous: None, energy: None, nudged_at: None }
+    }
+
+    /// Call with each usable frame.
+    pub fn update(&mut self, landmarks: &[Landmark], now: Instant) -> Stillness {
+        if let Some((previous, at)) = &self.previous {
+            let dt = now.duration_since(*at).as_secs_f32();
+            if let (Some(moved), true) = (displacement(previous, landmarks), dt > 0.0) {
+                let alpha = (dt / ENERGY_WINDOW).min(1.0);
+                let speed = moved / dt;
+                self.energy = Some(self.energy.map_or(speed, |e| e + alpha * (speed - e)));
+            }
+        }
+        self.previous = Some((landmarks.to_vec(), now));
+
+        let moved = self.anchor.as_ref()
+            .map(|(anchor, _)| displacement(anchor, landmarks).is_some_and(|d| d > self.config.tolerance))
+            .unwrap_or(true);
+        let ended = if moved { self.restart(landmarks, now) } else { None };
+
+        let still_for = self.anchor.as_ref().map_or(Duration::ZERO, |(_, since)| now.duration_since(*since));
+        let due = self.nudged_at.map_or(still_for, |at| now.duration_since(at));
+        let nudge = still_for >= self.config.nudge_after && due >= self.config.nudge_after;
+        if nudge {
+            self.nudged_at = Some(now);
+        }
+        Stillness { motion_energy: self.energy, still_for, ended, nudge }
+    }
+
+    /// The user left (which is movement too). Returns the still period this ends, if long.
+    pub fn reset(&mut self, now: Instant) -> Option<Duration> {
+        let ended = self.anchor.take().map(|(_, since)| now.duration_since(since));
+        *self = Self::new(self.config);
+        ended.filter(|d| *d >= MIN_STILL_PERIOD)
+    }
+
+    fn restart(&mut self, landmarks: &[Landmark], now: Instant) -> Option<Duration> {
+        let ended = self.anchor.replace((landmarks.to_vec(), now)).map(|(_, since)| now.duration_since(since));
+        self.nudged_at = None;
+        ended.filter(|d| *d >= MIN_STILL_PERIOD)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::mock::seated_user;
+
+    const FRAME: Duration = Duration::from_millis(500);
+
+    fn config() -> StillnessConfig {
+        StillnessConfig { nudge_after: Duration::from_secs(120), tolerance: 0.15 }
+    }
+
+    #[test]
+    fn holding_a_pose_earns_a_nudge_and_moving_ends_it() {
+        let mut monitor = StillnessMonitor::new(config());
+        let start = Instant::now();
+        let pose = seated_user(0.5);
+
+        // Two minutes of sitting still, with a little tremor
+        let nudges = (0..=240u32).filter(|&i| {
+            let mut frame = pose.clone();
+            frame[NOSE].x += if i % 2 == 0 { 0.002 } else { -0.002 };
+            monitor.update(&frame, start + FRAME * i).nudge
+        }).count();
+        assert_eq!(nudges, 1);
+
+        // Shifting in the chair starts a new still period and reports the old one
+        let update = monitor.update(&seated_user(0.6), start + FRAME * 241);
+        assert_eq!(update.ended, Some(FRAME * 241));
+        assert_eq!(update.still_for, Duration::ZERO);
+        assert!(!update.nudge);
+    }
+
+    #[test]
+    fn slow_drift_still_counts_as_moving() {
+        let mut monitor = StillnessMonitor::new(config());
+        let start = Instant::now();
+
+        // 0.002 a frame never looks like motion frame to frame, but adds up
+        let mut last = Stillness::default();
+        for i in 0..100u32 {
+            last = monitor.update(&seated_user(0.5 + 0.002 * i as f32), start + FRAME * i);
+        }
+        assert!(last.still_for < Duration::from_secs(30), "{:?}", last.still_for);
+        assert!(last.motion_energy.unwrap() < 0.02);
+    }
+
+    #[test]
+    fn nudges_repeat_while_the_user_stays_put() {
+        let mut monitor = StillnessMonitor::new(config());
+        let start = Instant::now();
+        let nudges = (0..=480u32).filter(|&i| monitor.update(&seated_user(0.5), start + FRAME * i).nudge).count();
+        assert_eq!(nudges, 2);
+        assert_eq!(monitor.reset(start + FRAME * 480), Some(Duration::from_secs(240)));
+        assert_eq!(monitor.reset(start + FRAME * 481), None);
+    }
+}
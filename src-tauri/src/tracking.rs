@@ -0,0 +1,600 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::Local;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use nokhwa::Camera;
+use nokhwa::pixel_format::RgbFormat;
+use nokhwa::utils::{CameraIndex, FrameFormat, RequestedFormat, RequestedFormatType};
+use std::sync::Arc;
+use crate::calibration::{self, Baseline};
+use crate::db;
+use crate::ergonomics::{ErgonomicsConfig, ErgonomicsMonitor, Setup};
+use crate::faults::{DetectedFault, Evidence, FaultClassifier, FaultConfig};
+use crate::frame_channel::FrameChannel;
+use crate::pose::{
+    quality, ColorMatrix, DepthMetrics, Frame, FrameQuality, HeadPose, Landmark, LandmarkSmoother, PixelFormat, PoseEngine,
+    PostureAngles, PrimarySelector, QualityConfig, QualityIssue, RoiTracker, Selection, SmoothingConfig,
+};
+use crate::posture_state::{PostureStateMachine, PostureStatus, PostureUpdate, Reading, StateConfig};
+use crate::session::{self, SessionConfig, SessionEvent, SessionTracker};
+use crate::scoring::{self, PostureScore, ScoringConfig};
+use crate::stance::{Stance, StanceConfig, StanceTracker};
+use crate::state::AppState;
+use crate::stillness::{MovementNudge, StillnessConfig, StillnessMonitor};
+
+// GLOBAL FLAG: Controls the background thread
+pub static IS_TRACKING: AtomicBool = AtomicBool::new(false);
+
+// "Active Correction" rate from ARCHITECTURE.md (15 FPS)
+const FRAME_INTERVAL: Duration = Duration::from_millis(66);
+// How often the worker reports throughput on `inference_stats`
+const STATS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Opens the default webcam into `AppState.camera` if it is not already open.
+/// Only `start` calls this, so the LED never comes on before tracking does.
+fn open_camera(state: &AppState) -> Result<String, String> {
+    let mut camera_lock = state.camera.lock().map_err(|_| "Failed to lock camera")?;
+    if camera_lock.is_some() { return Ok("Camera already open".to_string()); }
+
+    let format = RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestFrameRate);
+    let mut camera = Camera::new(CameraIndex::Index(0), format)
+        .map_err(|e| format!("Camera error: {}", e))?;
+    camera.open_stream().map_err(|e| format!("Stream error: {}", e))?;
+
+    *camera_lock = Some(camera);
+    Ok("Camera Ready".to_string())
+}
+
+/// HARDWARE KILL SWITCH: stops the loop and drops the camera so the OS cuts the LED.
+pub fn release_camera(state: &AppState) -> Result<(), String> {
+    stop(state);
+    let mut camera_lock = state.camera.lock().map_err(|_| "Failed to lock camera")?;
+    if let Some(mut camera) = camera_lock.take() {
+        let _ = camera.stop_stream();
+    }
+    Ok(())
+}
+
+/// Spawns the capture thread, which in turn owns the inference worker.
+/// Frames stay inside this process; only landmarks are emitted.
+pub fn start(app_handle: AppHandle, state: AppState) -> Result<String, String> {
+    let mut tracker_lock = state.tracker.lock().map_err(|_| "Failed to lock tracker")?;
+    if tracker_lock.is_some() && IS_TRACKING.load(Ordering::Relaxed) {
+        return Ok("Tracking already running".to_string());
+    }
+    // A previous loop that was flagged off may still be finishing its last frame
+    if let Some(stale) = tracker_lock.take() {
+        let _ = stale.join();
+    }
+
+    open_camera(&state)?;
+    IS_TRACKING.store(true, Ordering::Relaxed);
+
+    let loop_state = state.clone();
+    let handle = thread::Builder::new()
+        .name("posture-tracker".to_string())
+        .spawn(move || run_loop(app_handle, loop_state))
+        .map_err(|e| format!("Thread error: {}", e))?;
+
+    *tracker_lock = Some(handle);
+    Ok("Tracking Started".to_string())
+}
+
+/// Signals the capture thread to exit and waits for it so the camera is free afterwards.
+pub fn stop(state: &AppState) {
+    IS_TRACKING.store(false, Ordering::Relaxed);
+    // Joined under the lock, so `owns_sessions` keeps saying yes until the last session is stored
+    if let Ok(mut lock) = state.tracker.lock() {
+        if let Some(handle) = lock.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// True while a capture loop is alive, including one winding down after `stop`.
+/// For that whole time the loop starts, stores and ends sessions itself.
+pub fn owns_sessions(state: &AppState) -> bool {
+    IS_TRACKING.load(Ordering::Relaxed)
+        || state.tracker.lock().map(|lock| lock.as_ref().is_some_and(|h| !h.is_finished())).unwrap_or(true)
+}
+
+/// Emitted on `tracking_status` whenever it changes, so scoring can pause for bystanders.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrackingStatus {
+    pub people: usize,
+    pub multiple_people: bool,
+}
+
+/// Emitted on `inference_stats` every few seconds. Counters are totals since tracking started;
+/// latencies (capture to emit) cover the last interval only.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InferenceStats {
+    pub processed: u64,
+    pub dropped: u64,
+    pub mean_latency_ms: f64,
+    pub max_latency_ms: f64,
+}
+
+/// Emitted on `posture_frame` for every processed frame, next to the raw skeleton on `pose_update`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PostureFrame {
+    /// Smoothed metric landmarks, only for models with a world output (BlazePose).
+    pub world_landmarks: Option<Vec<Landmark>>,
+    pub depth: Option<DepthMetrics>,
+    pub head_pose: Option<HeadPose>,
+    pub angles: PostureAngles,
+    /// None while the frame quality check fails: we do not score what we cannot see.
+    pub score: Option<PostureScore>,
+    /// Eye height, viewing distance and offset relative to the camera.
+    pub setup: Option<Setup>,
+    pub stance: Stance,
+    /// Head-and-shoulder speed in shoulder widths per second, and time spent in this pose.
+    pub motion_energy: Option<f32>,
+    pub still_sec: u64,
+}
+
+/// Everything the pipeline learned from one frame.
+struct Observation {
+    selection: Selection,
+    quality: FrameQuality,
+    depth: Option<DepthMetrics>,
+    head_pose: Option<HeadPose>,
+    angles: PostureAngles,
+    score: Option<PostureScore>,
+    setup: Option<Setup>,
+    faults: Evidence,
+    stance: Stance,
+}
+
+/// Per-run pipeline state. Lives on the inference worker only and never holds frame data.
+struct Pipeline {
+    roi: RoiTracker,
+    selector: PrimarySelector,
+    smoother: LandmarkSmoother,
+    world_smoother: LandmarkSmoother,
+    quality: QualityConfig,
+    scoring: ScoringConfig,
+    ergonomics: ErgonomicsConfig,
+    stance: StanceTracker,
+    baseline: Option<Arc<Baseline>>,
+    clock: Instant,
+}
+
+impl Pipeline {
+    fn new(settings: &HashMap<String, String>) -> Self {
+        Self {
+            roi: RoiTracker::new(),
+            selector: PrimarySelector::new(),
+            smoother: LandmarkSmoother::new(SmoothingConfig::from_settings(settings)),
+            world_smoother: LandmarkSmoother::new(SmoothingConfig::from_settings(settings)),
+            quality: QualityConfig::from_settings(settings),
+            scoring: ScoringConfig::from_settings(settings),
+            ergonomics: ErgonomicsConfig::from_settings(settings),
+            stance: StanceTracker::new(StanceConfig::from_settings(settings)),
+            baseline: None,
+            clock: Instant::now(),
+        }
+    }
+
+    /// Scores relative to `baseline` from now on, and lets the selector fall back to where the
+    /// user sat during calibration.
+    fn set_baseline(&mut self, baseline: Option<Arc<Baseline>>) {
+        let unchanged = match (&self.baseline, &baseline) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged { return; }
+        self.selector.set_baseline(baseline.as_ref().and_then(|b| b.anchor()));
+        self.baseline = baseline;
+    }
+
+    fn process(&mut self, engine: &PoseEngine, frame: &Frame) -> Result<Observation, String> {
+        let img = frame.to_rgb()?;
+
+        // Crop around last frame's skeleton; if the crop lost the user, retry on the whole frame
+        let roi = self.roi.roi();
+        let mut poses = engine.infer_rgb(&img, roi)?;
+        if roi.is_some() && !poses.iter().any(|p| RoiTracker::is_tracked(&p.landmarks)) {
+            poses = engine.infer_rgb(&img, None)?;
+        }
+
+        let mut selection = self.selector.select(poses);
+        self.roi.update(&selection.primary);
+
+        // Raw landmarks jitter frame to frame; never emit them unfiltered
+        let t = self.clock.elapsed().as_secs_f64();
+        selection.primary = self.smoother.smooth(&selection.primary, t);
+        selection.world = selection.world.map(|world| self.world_smoother.smooth(&world, t));
+
+        // Judged on the smoothed skeleton, so one noisy frame does not flip the verdict
+        let mut quality = quality::assess(&img, &selection.primary, &self.quality);
+        // A bystander's slouch must not land in the user's session; pause until they leave
+        if selection.multiple_people() && quality.issue.is_none() {
+            quality.issue = Some(QualityIssue::MultiplePeople);
+            quality.message = Some(QualityIssue::MultiplePeople.message().to_string());
+        }
+        let depth = selection.world.as_deref().and_then(DepthMetrics::measure);
+        let (width, height) = img.dimensions();
+        let aspect = width as f32 / height as f32;
+        let head_pose = HeadPose::estimate(&selection.primary, aspect);
+        let angles = PostureAngles::measure(&selection.primary, selection.world.as_deref(), head_pose.as_ref(), aspect);
+        self.stance.update(Stance::classify(&selection.primary, self.baseline.as_deref()), Instant::now());
+        let stance = self.stance.stance();
+        let score = quality.is_usable()
+            .then(|| {
+                let baseline = self.baseline.as_ref().map(|b| &b.angles);
+                scoring::score(&selection.primary, &angles, baseline, stance, (width, height), &self.scoring)
+            });
+        let setup = Setup::estimate(&selection.primary, head_pose.as_ref(), aspect, &self.ergonomics);
+        let faults = Evidence::measure(&selection.primary, &angles, depth.as_ref(), self.baseline.as_deref(), aspect);
+        Ok(Observation { selection, quality, depth, head_pose, angles, score, setup, faults, stance })
+    }
+}
+
+// A frame plus when it left the camera, so the worker can measure end-to-end latency
+struct Captured {
+    frame: Frame,
+    at: Instant,
+}
+
+/// Logs and emits a failure only when it differs from the previous one,
+/// otherwise we flood the webview at 15 FPS.
+#[derive(Default)]
+struct ErrorReporter {
+    last: Option<String>,
+}
+
+impl ErrorReporter {
+    fn report(&mut self, app_handle: &AppHandle, error: String) {
+        if self.last.as_deref() != Some(error.as_str()) {
+            println!("⚠️ Tracking: {}", error);
+            let _ = app_handle.emit("tracking_debug", error.clone());
+            self.last = Some(error);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.last = None;
+    }
+}
+
+// Capture side: grabs frames at FRAME_INTERVAL and never waits on the model.
+// A slow inference just means the worker skips to the newest frame.
+fn run_loop(app_handle: AppHandle, state: AppState) {
+    println!("🎥 Tracking loop started");
+    let channel = FrameChannel::new();
+    let settings = db::current_settings(&state);
+    calibration::load_into_state(&state);
+
+    thread::scope(|scope| {
+        let worker = thread::Builder::new()
+            .name("pose-inference".to_string())
+            .spawn_scoped(scope, || inference_worker(&app_handle, &state, &channel, &settings));
+        if let Err(e) = worker {
+            ErrorReporter::default().report(&app_handle, format!("Thread error: {}", e));
+            IS_TRACKING.store(false, Ordering::Relaxed);
+            return;
+        }
+
+        let mut errors = ErrorReporter::default();
+        while IS_TRACKING.load(Ordering::Relaxed) {
+            let started = Instant::now();
+
+            match capture_frame(&state) {
+                Ok(frame) => {
+                    errors.clear();
+                    channel.publish(Captured { frame, at: Instant::now() });
+                }
+                Err(e) => errors.report(&app_handle, e),
+            }
+
+            if let Some(remaining) = FRAME_INTERVAL.checked_sub(started.elapsed()) {
+                thread::sleep(remaining);
+            }
+        }
+
+        // Wakes the worker; the scope joins it before we return
+        channel.close();
+    });
+
+    println!("🛑 Tracking loop stopped");
+}
+
+// Inference side: the only caller of the pose engine while tracking runs.
+fn inference_worker(app_handle: &AppHandle, state: &AppState, channel: &FrameChannel<Captured>, settings: &HashMap<String, String>) {
+    let mut pipeline = Pipeline::new(settings);
+    let mut errors = ErrorReporter::default();
+    let mut last_status: Option<TrackingStatus> = None;
+    let mut last_issue: Option<Option<QualityIssue>> = None;
+    let mut posture = PostureStateMachine::new(StateConfig::from_settings(settings));
+    let session_config = SessionConfig::from_settings(settings);
+    let mut sessions = SessionTracker::new(session_config);
+    let mut ergonomics = ErgonomicsMonitor::new(pipeline.ergonomics);
+    let mut faults = FaultClassifier::new(FaultConfig::from_settings(settings));
+    let mut last_faults: Vec<DetectedFault> = Vec::new();
+    let mut last_stance = Stance::Sitting;
+    let mut stillness = StillnessMonitor::new(StillnessConfig::from_settings(settings));
+
+    let mut stats = InferenceStats::default();
+    let mut latencies: Vec<f64> = Vec::new();
+    let mut last_report = Instant::now();
+
+    while let Some(captured) = channel.recv() {
+        // Picks up a finished calibration, or a cleared one
+        let baseline = state.calibration.lock().ok().and_then(|c| c.baseline.clone());
+        pipeline.set_baseline(baseline);
+
+        match pipeline.process(&state.pose_engine, &captured.frame) {
+            Ok(Observation { selection, quality, depth, head_pose, angles, score, setup, faults: evidence, stance }) => {
+                errors.clear();
+
+                // Tells the user why nothing is being scored ("too dark", "move back")
+                let issue = quality.issue;
+                if last_issue != Some(issue) {
+                    let _ = app_handle.emit("frame_quality", quality);
+                    last_issue = Some(issue);
+                }
+
+                let status = TrackingStatus { people: selection.people, multiple_people: selection.multiple_people() };
+                if last_status.as_ref() != Some(&status) {
+                    let _ = app_handle.emit("tracking_status", status.clone());
+                    last_status = Some(status);
+                }
+
+                // Only frames we would score count towards the session
+                if let Ok(mut breakdown) = state.breakdown.lock() {
+                    if let (None, Some(pose)) = (issue, &head_pose) {
+                        breakdown.record_head_pose(pose);
+                    }
+                    if let Some(score) = &score {
+                        breakdown.record_score(score);
+                    }
+                }
+                let now = Instant::now();
+                let calibrated = calibration::observe(app_handle, state, &selection.primary, &angles, issue.is_none());
+
+                // Checked on the calibration frames, then every so often
+                let mut alerts = ergonomics.observe(setup.filter(|_| issue.is_none()), now);
+                if calibrated {
+                    alerts = ergonomics.check_now(now);
+                }
+                for alert in alerts {
+                    println!("🪑 {}", alert.message);
+                    let _ = app_handle.emit("calibration-alert", alert);
+                }
+
+                // Named faults per window; the webview hears when the set changes
+                let window = match issue {
+                    None => faults.push(&evidence, now),
+                    Some(_) => { faults.skip(); None }
+                };
+                if let Some(window) = window {
+                    if let Ok(mut breakdown) = state.breakdown.lock() {
+                        breakdown.record_faults(&window);
+                    }
+                    let kinds = |list: &[DetectedFault]| list.iter().map(|f| f.fault).collect::<Vec<_>>();
+                    if kinds(&window.faults) != kinds(&last_faults) {
+                        for detected in window.faults.iter().filter(|f| !kinds(&last_faults).contains(&f.fault)) {
+                            println!("🔎 {}", detected.fault.message());
+                        }
+                        let _ = app_handle.emit("posture_faults", window.faults.clone());
+                        last_faults = window.faults;
+                    }
+                }
+
+                // A dark room hides the user without them having left
+                let reading = match (issue, &score) {
+                    (_, Some(score)) => Reading::Scored(score.total),
+                    (Some(QualityIssue::TooDark), _) => Reading::Unusable,
+                    _ if !session::is_present(&selection.primary, &session_config) => Reading::Absent,
+                    _ => Reading::Unusable,
+                };
+                if let Some(status) = posture.update(reading, now) {
+                    println!("🧍 Posture: {:?}", status);
+                    let update = PostureUpdate::new(&posture, &selection.primary, pipeline.baseline.is_some());
+                    let _ = app_handle.emit("posture-update", update);
+                }
+                // Stillness only counts while someone is there; leaving is movement too
+                let still = match (posture.status(), issue) {
+                    (PostureStatus::Away, _) => {
+                        if let Some(period) = stillness.reset(now) {
+                            if let Ok(mut breakdown) = state.breakdown.lock() {
+                                breakdown.record_still_period(period);
+                            }
+                        }
+                        None
+                    }
+                    (_, None) => Some(stillness.update(&selection.primary, now)),
+                    _ => None,
+                };
+                if let Some(still) = &still {
+                    if let Ok(mut breakdown) = state.breakdown.lock() {
+                        if let Some(energy) = still.motion_energy {
+                            breakdown.record_motion(energy);
+                        }
+                        if let Some(period) = still.ended {
+                            breakdown.record_still_period(period);
+                        }
+                        if still.nudge {
+                            breakdown.record_nudge();
+                        }
+                    }
+                    if still.nudge {
+                        let still_minutes = still.still_for.as_secs() / 60;
+                        println!("🤸 Still for {} min, nudging", still_minutes);
+                        let _ = app_handle.emit("movement_nudge", MovementNudge {
+                            still_minutes,
+                            message: format!("You have barely moved for {} minutes - stretch or shift position", still_minutes),
+                        });
+                    }
+                }
+
+                if stance != last_stance {
+                    println!("🚶 Stance: {:?}", stance);
+                    let _ = app_handle.emit("stance_changed", stance);
+                    last_stance = stance;
+                }
+                if let Some(event) = sessions.update(posture.status(), score.as_ref().map(|s| s.total), stance, now, Local::now()) {
+                    session::handle_event(app_handle, state, event);
+                }
+
+                let _ = app_handle.emit("posture_frame", PostureFrame {
+                    world_landmarks: selection.world, depth, head_pose, angles, score, setup, stance,
+                    motion_energy: still.and_then(|s| s.motion_energy),
+                    still_sec: still.map_or(0, |s| s.still_for.as_secs()),
+                });
+                let _ = app_handle.emit("pose_update", selection.primary);
+            }
+            Err(e) => errors.report(app_handle, e),
+        }
+        stats.processed += 1;
+        latencies.push(captured.at.elapsed().as_secs_f64() * 1000.0);
+
+        if last_report.elapsed() >= STATS_INTERVAL {
+            stats.dropped = channel.dropped();
+            stats.mean_latency_ms = latencies.iter().sum::<f64>() / latencies.len() as f64;
+            stats.max_latency_ms = latencies.iter().copied().fold(0.0, f64::max);
+            let _ = app_handle.emit("inference_stats", stats.clone());
+            latencies.clear();
+            last_report = Instant::now();
+        }
+    }
+
+    if let Some(period) = stillness.reset(Instant::now()) {
+        if let Ok(mut breakdown) = state.breakdown.lock() {
+            breakdown.record_still_period(period);
+        }
+    }
+    // Tracking stopped with the user still there: the session ends now
+    if sessions.is_active() {
+        session::handle_event(app_handle, state, SessionEvent::Ended(sessions.finish()));
+    }
+    println!("📊 Inference worker done: {} frames processed, {} dropped", stats.processed, channel.dropped());
+}
+
+/// Grabs one frame. The camera lock is released on return, so it is never held during inference.
+fn capture_frame(state: &AppState) -> Result<Frame, String> {
+    let mut camera_lock = state.camera.lock().map_err(|_| "Failed to lock camera")?;
+    let camera = camera_lock.as_mut().ok_or("Camera not initialized")?;
+    let buffer = camera.frame().map_err(|e| format!("Frame error: {}", e))?;
+    let (width, height) = (buffer.resolution().width(), buffer.resolution().height());
+
+    let format = match buffer.source_frame_format() {
+        FrameFormat::MJPEG => Some(PixelFormat::Mjpeg),
+        FrameFormat::YUYV => Some(PixelFormat::Yuyv),
+        FrameFormat::NV12 => Some(PixelFormat::Nv12),
+        FrameFormat::GRAY => Some(PixelFormat::Gray),
+        FrameFormat::RAWRGB => Some(PixelFormat::Rgb),
+        _ => None,
+    };
+
+    match format {
+        Some(format) => {
+            let mut frame = Frame::new(buffer.buffer().to_vec(), width, height, format);
+            frame.matrix = ColorMatrix::for_height(height);
+            Ok(frame)
+        }
+        // Anything we have no native path for goes through nokhwa's own decoder
+        None => {
+            let rgb = buffer.decode_image::<RgbFormat>().map_err(|e| format!("Decode error: {}", e))?;
+            Ok(Frame::new(rgb.into_raw(), width, height, PixelFormat::Rgb))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pose::mock::{seated_user, test_entry, MockEstimator};
+    use crate::pose::DecodedPose;
+
+    fn engine_with(mock: MockEstimator) -> PoseEngine {
+        let engine = PoseEngine::new();
+        engine.install(Box::new(mock), test_entry("mock")).unwrap();
+        engine
+    }
+
+    fn grey_frame(level: u8) -> Frame {
+        Frame::new(vec![level; 64 * 48 * 3], 64, 48, PixelFormat::Rgb)
+    }
+
+    // 8 px black and white stripes: bright and sharp enough to score
+    fn striped_frame() -> Frame {
+        let data = (0..64 * 48).flat_map(|i| [if (i % 64) / 8 % 2 == 0 { 255 } else { 0 }; 3]).collect();
+        Frame::new(data, 64, 48, PixelFormat::Rgb)
+    }
+
+    #[test]
+    fn pipeline_picks_the_centred_user_over_a_bystander() {
+        let both = vec![
+            DecodedPose { landmarks: seated_user(0.85), score: 0.9, world: None },
+            DecodedPose { landmarks: seated_user(0.5), score: 0.9, world: None },
+        ];
+        let engine = engine_with(MockEstimator::new(vec![both]));
+        let mut pipeline = Pipeline::new(&HashMap::new());
+
+        let observation = pipeline.process(&engine, &grey_frame(128)).unwrap();
+        assert_eq!(observation.selection.people, 2);
+        let nose = &observation.selection.primary[crate::pose::keypoints::NOSE];
+        assert!((nose.x - 0.5).abs() < 1e-3, "picked the bystander at x={}", nose.x);
+    }
+
+    #[test]
+    fn bystanders_pause_scoring() {
+        let alone = engine_with(MockEstimator::constant(seated_user(0.5)));
+        let observation = Pipeline::new(&HashMap::new()).process(&alone, &striped_frame()).unwrap();
+        assert_eq!(observation.quality.issue, None);
+        assert!(observation.score.is_some());
+
+        let both = vec![
+            DecodedPose { landmarks: seated_user(0.85), score: 0.9, world: None },
+            DecodedPose { landmarks: seated_user(0.5), score: 0.9, world: None },
+        ];
+        let crowded = engine_with(MockEstimator::new(vec![both]));
+        let observation = Pipeline::new(&HashMap::new()).process(&crowded, &striped_frame()).unwrap();
+        assert_eq!(observation.quality.issue, Some(QualityIssue::MultiplePeople));
+        assert!(!observation.quality.is_usable());
+        assert!(observation.score.is_none());
+    }
+
+    #[test]
+    fn world_landmarks_give_depth_metrics() {
+        // Ears 6 cm closer to the camera than the shoulders, same height difference as the image
+        let world: Vec<Landmark> = seated_user(0.0).into_iter().enumerate().map(|(i, lm)| {
+            let z = if i == crate::pose::keypoints::LEFT_EAR || i == crate::pose::keypoints::RIGHT_EAR { -0.06 } else { 0.0 };
+            Landmark { x: lm.x * 0.5, y: (lm.y - 0.92) * 0.5, z, visibility: lm.visibility }
+        }).collect();
+        let pose = DecodedPose { landmarks: seated_user(0.5), score: 0.9, world: Some(world) };
+        let engine = engine_with(MockEstimator::new(vec![vec![pose]]));
+        let mut pipeline = Pipeline::new(&HashMap::new());
+
+        let depth = pipeline.process(&engine, &grey_frame(128)).unwrap().depth.unwrap();
+        assert!((depth.head_forward_cm - 6.0).abs() < 1e-3);
+        assert!(depth.neck_flexion_deg > 0.0);
+        assert!(depth.shoulder_rotation_cm.abs() < 1e-3);
+    }
+
+    #[test]
+    fn dark_frames_are_not_scored() {
+        let engine = engine_with(MockEstimator::constant(seated_user(0.5)));
+        let mut pipeline = Pipeline::new(&HashMap::new());
+
+        let observation = pipeline.process(&engine, &grey_frame(10)).unwrap();
+        assert_eq!(observation.quality.issue, Some(QualityIssue::TooDark));
+        assert!(observation.score.is_none());
+    }
+
+    #[test]
+    fn missing_user_is_reported_as_nobody_in_view() {
+        let engine = engine_with(MockEstimator::new(vec![vec![]]));
+        let mut pipeline = Pipeline::new(&HashMap::new());
+
+        let observation = pipeline.process(&engine, &grey_frame(128)).unwrap();
+        assert!(observation.selection.primary.is_empty());
+        assert_eq!(observation.quality.issue, Some(QualityIssue::NoPerson));
+    }
+}
@@ -55,11 +55,8 @@ function App() {
         await bridge.initDb();
         console.log("✅ Database Ready");
 
-        console.log("2. Initializing Camera...");
-        await bridge.initCamera();
-        console.log("✅ Camera Ready");
-
-        console.log("3. Initializing AI Engine...");
+        // The camera stays closed until tracking starts (Privacy by Default)
+        console.log("2. Initializing AI Engine...");
         await bridge.initAi();
         console.log("✅ AI Engine Ready");
 
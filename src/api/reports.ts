@@ -20,7 +20,8 @@ export async function fetchReport(range: ReportRange): Promise<ReportSummary> {
             avg_score: 0,
             best_streak: 0,
             current_streak: 0,
-            graph_data: []
+            graph_data: [],
+            faults: []
         };
     }
 }
@@ -75,10 +75,21 @@ export const bridge = {
 
     // --- AI Engine ---
     initAi: async () => safeInvoke<string>("init_ai"),
+    listModels: async () => safeInvoke<ModelInfo[]>("list_models"),
+    switchModel: async (name: string) => safeInvoke<string>("switch_model", { name }),
+    benchmarkModel: async (iterations?: number, model?: string) =>
+        safeInvoke<BenchmarkReport>("benchmark_model", { iterations, model }),
     startTracking: async () => safeInvoke<string>("start_tracking"),
     stopTracking: async () => safeInvoke<string>("stop_tracking"),
 
+    // --- Calibration ---
+    calibrate: async (durationSec?: number) =>
+        safeInvoke<string>("calibrate", { durationSec }),
+    getCalibration: async () => safeInvoke<CalibrationStatus>("get_calibration"),
+    clearCalibration: async () => safeInvoke<string>("clear_calibration"),
+
     // --- Session Management ---
+    // Tracking records sessions on its own (see onSessionEnded) and this is refused while it runs
     saveSession: async (session: SessionSummary) =>
         safeInvoke<string>("save_session", { session }),
 
@@ -117,9 +128,208 @@ export const bridge = {
         return listen<string>("tracking_debug", (event) => {
             callback(event.payload);
         });
+    },
+
+    onTrackingStatus: (callback: (status: TrackingStatus) => void) => {
+        return listen<TrackingStatus>("tracking_status", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onFrameQuality: (callback: (quality: FrameQuality) => void) => {
+        return listen<FrameQuality>("frame_quality", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onInferenceStats: (callback: (stats: InferenceStats) => void) => {
+        return listen<InferenceStats>("inference_stats", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onPostureFrame: (callback: (frame: PostureFrame) => void) => {
+        return listen<PostureFrame>("posture_frame", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onPostureUpdate: (callback: (update: PostureUpdate) => void) => {
+        return listen<PostureUpdate>("posture-update", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    // Current faults, whenever the set changes (empty once they are fixed)
+    onPostureFaults: (callback: (faults: DetectedFault[]) => void) => {
+        return listen<DetectedFault[]>("posture_faults", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onStanceChanged: (callback: (stance: Stance) => void) => {
+        return listen<Stance>("stance_changed", (event) => {
+            callback(event.payload);
+        });
+    },
+    onMovementNudge: (callback: (nudge: MovementNudge) => void) => {
+        return listen<MovementNudge>("movement_nudge", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onSessionStarted: (callback: (id: string) => void) => {
+        return listen<string>("session_started", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onSessionPaused: (callback: () => void) => {
+        return listen<null>("session_paused", () => {
+            callback();
+        });
+    },
+
+    onSessionResumed: (callback: (gap: PresenceGap) => void) => {
+        return listen<PresenceGap>("session_resumed", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    // null when the session was too short to keep
+    onSessionEnded: (callback: (session: SessionSummary | null) => void) => {
+        return listen<SessionSummary | null>("session_ended", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onCalibrationComplete: (callback: (status: CalibrationStatus) => void) => {
+        return listen<CalibrationStatus>("calibration_complete", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onCalibrationFailed: (callback: (message: string) => void) => {
+        return listen<string>("calibration_failed", (event) => {
+            callback(event.payload);
+        });
+    },
+
+    onCalibrationAlert: (callback: (alert: CalibrationAlert) => void) => {
+        return listen<CalibrationAlert>("calibration-alert", (event) => {
+            callback(event.payload);
+        });
     }
 };
 
+export interface ModelInfo {
+    name: string;
+    version: string;
+    file: string;
+    input_layout: string | null;
+    output_layout: string | null;
+    sha256: string;
+    active: boolean;
+}
+
+export interface BenchmarkReport {
+    estimator: string; // backend + negotiated input, e.g. "tract 256x256 Nhwc F32 blazepose"
+    iterations: number;
+    p50_ms: number;
+    p95_ms: number;
+    mean_ms: number;
+    config: {
+        intra_threads: number;
+        inter_threads: number;
+        optimization_level: "disable" | "basic" | "extended" | "layout" | "all";
+        memory_arena: boolean;
+        parallel_execution: boolean;
+    };
+}
+
+export type QualityIssue = "too_dark" | "no_person" | "shoulders_out_of_frame" | "occluded" | "too_blurry" | "multiple_people";
+
+// Emitted when the verdict changes; `issue === null` means frames are being scored again
+export interface FrameQuality {
+    luminance: number;
+    sharpness: number;
+    key_visible: number;
+    shoulders_in_frame: boolean;
+    issue: QualityIssue | null;
+    message: string | null;
+}
+
+export interface InferenceStats {
+    processed: number;
+    dropped: number;
+    mean_latency_ms: number;
+    max_latency_ms: number;
+}
+
+export interface TrackingStatus {
+    people: number;
+    multiple_people: boolean;
+}
+
+// Measured on BlazePose world landmarks; null for models without a world output
+export interface DepthMetrics {
+    head_forward_cm: number;
+    neck_flexion_deg: number;
+    trunk_lean_deg: number | null;
+    shoulder_rotation_cm: number;
+}
+
+// Degrees; pitch > 0 is looking down, yaw > 0 turned to the user's right, roll > 0 towards the left shoulder
+export interface HeadPose {
+    pitch_deg: number;
+    yaw_deg: number;
+    roll_deg: number;
+}
+
+// Degrees; null when the landmarks an angle needs are not visible
+export interface PostureAngles {
+    craniovertebral_deg: number | null;
+    neck_flexion_deg: number | null;
+    shoulder_tilt_deg: number | null;
+    trunk_inclination_deg: number | null;
+    shoulder_protraction_deg: number | null;
+}
+
+// Computed by the backend (scoring.rs); same parts as PostureMetrics in postureMath.ts
+export interface PostureScore {
+    total: number;
+    neck: number;
+    shoulders: number;
+    spine: number;
+    is_good: boolean;
+}
+
+export interface PostureFrame {
+    world_landmarks: Landmark[] | null;
+    depth: DepthMetrics | null;
+    head_pose: HeadPose | null;
+    angles: PostureAngles;
+    score: PostureScore | null;
+    setup: ErgonomicSetup | null;
+    stance: Stance;
+    motion_energy: number | null; // shoulder widths per second
+    still_sec: number;
+}
+
+export type Stance = "sitting" | "standing";
+
+export interface MovementNudge {
+    still_minutes: number;
+    message: string;
+}
+
+// Centimetres relative to the camera, assumed at the top centre of the screen
+export interface ErgonomicSetup {
+    eye_height_cm: number;
+    distance_cm: number;
+    lateral_offset_cm: number;
+}
+
 export interface SessionSummary {
     id: string; // UUID
     start_time: string; // ISO
@@ -129,6 +339,9 @@ export interface SessionSummary {
     good_time_sec: number;
     bad_time_sec: number;
     breakdown_json: string;
+    model_version?: string | null; // "name@version", filled in by the backend
+    standing_sec?: number; // part of duration_sec spent standing
+    stance_changes?: number; // sit/stand transitions
 }
 
 export interface DashboardStats {
@@ -159,6 +372,8 @@ export interface ReportDataPoint {
     name: string;
     score: number;
     focus: number;
+    standing: number; // minutes
+    stance_changes: number;
 }
 
 export interface ReportSummary {
@@ -167,4 +382,50 @@ export interface ReportSummary {
     best_streak: number;
     current_streak: number;
     graph_data: ReportDataPoint[];
+    faults: FaultTime[]; // most time first
+}
+
+export type Fault = "forward_head" | "rounded_shoulders" | "lean_left" | "lean_right"
+    | "slumped_trunk" | "chin_on_hand" | "twisted_torso";
+
+export interface FaultTime {
+    fault: Fault;
+    seconds: number;
+}
+
+export interface DetectedFault {
+    fault: Fault;
+    confidence: number; // 0-1
+}
+
+export type PostureStatus = "Good" | "Bad" | "Unknown" | "Away";
+
+export interface PostureUpdate {
+    status: PostureStatus;
+    score: number | null; // 0-1, only while Good or Bad
+    privacy_hash: string;
+    is_calibrated: boolean;
+}
+
+export interface PresenceGap {
+    start: string;
+    end: string;
+    duration_sec: number;
+}
+
+export interface CalibrationStatus {
+    is_calibrated: boolean;
+    calibrating: boolean;
+    created_at: string | null;
+    age_hours: number | null;
+    samples: number;
+    model_version: string | null;
+    angles: PostureAngles | null;
+    camera_drifted: boolean;
+}
+
+export interface CalibrationAlert {
+    kind: "camera_moved" | "monitor_too_low" | "monitor_too_high" | "too_close" | "too_far" | "off_centre";
+    message: string;
+    suggestion: string;
 }
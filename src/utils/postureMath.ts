@@ -1,6 +1,8 @@
 /**
  * postureMath.ts
  * The Brain: Calculates posture metrics from TensorFlow/MoveNet keypoints.
+ * The backend port in src-tauri/src/scoring.rs is the source of truth: it scores the native
+ * tracking loop and sends results on `posture_frame`. Keep the two in step until this goes.
  */
 
 export interface PostureMetrics {